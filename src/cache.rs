@@ -0,0 +1,104 @@
+//! The latest successful reading kept by the driver, so displays can show it without
+//! touching the bus and a brief run of failures doesn't blank them. Time comes from the
+//! driver's [Clock], see [SHT31::with_clock]
+use crate::{clock::Clock, Reading, SHT31};
+
+/// Cached reading and the millisecond timestamp it was taken at
+#[derive(Default, Copy, Clone, Debug)]
+pub(crate) struct ReadingCache {
+    enabled: bool,
+    entry: Option<(Reading, u32)>,
+}
+
+impl ReadingCache {
+    /// Keeps the reading taken at `now`, readings are only cached by a driver with a clock
+    pub(crate) fn store(&mut self, reading: Reading, now: Option<u32>) {
+        if let (true, Some(now)) = (self.enabled, now) {
+            self.entry = Some((reading, now));
+        }
+    }
+}
+
+impl<Mode, I2C, C> SHT31<Mode, I2C, C> {
+    /// The latest successful reading, [None] until one is taken or while caching is disabled
+    pub fn last_reading(&self) -> Option<Reading> {
+        self.cache.entry.map(|(reading, _)| reading)
+    }
+}
+
+impl<Mode, I2C, C> SHT31<Mode, I2C, C>
+where
+    C: Clock,
+{
+    /// Keep the latest successful reading, stamped by the driver's clock.
+    /// Disabling caching drops the cached reading
+    pub fn set_cache_readings(&mut self, enabled: bool) {
+        self.cache = ReadingCache {
+            enabled,
+            entry: None,
+        };
+    }
+
+    /// Keep the latest successful reading, stamped by the driver's clock
+    pub fn with_cache_readings(mut self) -> Self {
+        self.set_cache_readings(true);
+        self
+    }
+
+    /// Milliseconds since [SHT31::last_reading] was taken, by the driver's clock
+    pub fn age(&mut self) -> Option<u32> {
+        let (_, taken_at) = self.cache.entry?;
+        Some(self.clock.now_ms().wrapping_sub(taken_at))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use crate::{prelude::*, test_utils};
+    use alloc::vec;
+    use core::cell::Cell;
+    use embedded_hal::i2c::ErrorKind;
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock, Transaction},
+    };
+
+    #[test]
+    fn keeps_last_reading() {
+        let now = Cell::new(0);
+        let frame = [98, 153, 188, 98, 32, 139];
+        let mut expectations =
+            test_utils::single_shot_expectations(0x44, Accuracy::High, false, frame).to_vec();
+        expectations.extend(test_utils::single_shot_expectations(
+            0x44,
+            Accuracy::High,
+            false,
+            frame,
+        ));
+        expectations.push(Transaction::write(0x44, vec![0x24, 0x00]).with_error(ErrorKind::Other));
+        let mut sensor = SHT31::single_shot(Mock::new(&expectations), SingleShot::new())
+            .with_clock(|| now.get());
+        let mut delay = NoopDelay::new();
+
+        sensor.measure_and_wait(&mut delay).unwrap();
+        assert_eq!(sensor.last_reading(), None);
+
+        sensor.set_cache_readings(true);
+        now.set(1_000);
+        let reading = sensor.measure_and_wait(&mut delay).ok();
+        now.set(1_250);
+        assert!(sensor.measure_and_wait(&mut delay).is_err());
+        assert_eq!(sensor.last_reading(), reading);
+        assert_eq!(sensor.age(), Some(250));
+
+        // Ages without the sensor being read
+        now.set(3_000);
+        assert_eq!(sensor.age(), Some(2_000));
+
+        sensor.set_cache_readings(false);
+        assert_eq!(sensor.age(), None);
+
+        sensor.destroy().done();
+    }
+}
@@ -0,0 +1,63 @@
+//! CRC-8 backends verifying the checksum the sensor sends with every word
+use crc::{Algorithm, Crc};
+
+const CRC_ALGORITHM: Algorithm<u8> = Algorithm {
+    width: 8,
+    poly: 0x31,
+    init: 0xFF,
+    refin: false,
+    refout: false,
+    xorout: 0x00,
+    check: 0x00,
+    residue: 0x00,
+};
+
+/// Built once so checksumming a reading is just the digest over the precomputed table
+static CRC: Crc<u8> = Crc::<u8>::new(&CRC_ALGORITHM);
+
+/// How a reading whose checksum doesn't match is handled
+#[derive(Default, Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChecksumPolicy {
+    /// The reading is dropped and the mismatch returned as an error
+    #[default]
+    Strict,
+    /// The reading is returned anyway and flagged, see
+    /// [SHT31::last_checksum_failed](crate::SHT31::last_checksum_failed)
+    Lenient,
+    /// Checksums aren't calculated at all
+    Skip,
+}
+
+/// A CRC-8 implementation. It's stateless so the sensor stays `Copy` without another type
+/// parameter, which means there's no instance to hold a peripheral. A hardware CRC unit
+/// has to be reached through a global the application sets up before the first reading,
+/// e.g. a `critical_section::Mutex<RefCell<Option<Crc>>>`
+pub trait Checksum {
+    /// CRC-8 of the word with polynomial 0x31, initial value 0xFF and no final XOR
+    fn checksum(word: [u8; 2]) -> u8;
+}
+
+/// The bundled table driven software CRC, used by default
+#[derive(Copy, Clone, Debug)]
+pub struct SoftwareCrc;
+
+impl Checksum for SoftwareCrc {
+    fn checksum(word: [u8; 2]) -> u8 {
+        let mut digest = CRC.digest();
+        digest.update(&word);
+        digest.finalize()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn software_crc() {
+        // Example from the datasheet
+        assert_eq!(SoftwareCrc::checksum([0xBE, 0xEF]), 0x92);
+    }
+}
@@ -0,0 +1,165 @@
+//! Timestamps from an application supplied clock, in the same wrapping millisecond
+//! counts the rest of the driver takes. The driver keeps its own [Clock], set with
+//! [crate::SHT31::with_clock], for the heater guard, the heater decay and the reading cache
+use crate::{error::Result, logger::LogEntry, mode::Sht31Reader, Reading};
+
+/// A monotonic millisecond counter, expected to wrap around at `u32::MAX`.
+/// Closures such as `|| millis()` are clocks
+pub trait Clock {
+    fn now_ms(&mut self) -> u32;
+}
+
+impl<F> Clock for F
+where
+    F: FnMut() -> u32,
+{
+    fn now_ms(&mut self) -> u32 {
+        self()
+    }
+}
+
+/// The clock a driver starts with, the parts that need the time stay off until
+/// a [Clock] is given
+#[derive(Default, Copy, Clone, Debug)]
+pub struct NoClock;
+
+/// A clock the driver may or may not have, [NoClock] never tells the time
+pub trait MaybeClock {
+    fn try_now_ms(&mut self) -> Option<u32>;
+}
+
+impl<C: Clock> MaybeClock for C {
+    fn try_now_ms(&mut self) -> Option<u32> {
+        Some(self.now_ms())
+    }
+}
+
+impl MaybeClock for NoClock {
+    fn try_now_ms(&mut self) -> Option<u32> {
+        None
+    }
+}
+
+/// A reading and the millisecond timestamp it was taken at
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimestampedReading {
+    pub reading: Reading,
+    pub timestamp: u32,
+}
+
+impl TimestampedReading {
+    /// Milliseconds since an earlier reading, across the clock wrapping around
+    pub fn elapsed_since(&self, earlier: &TimestampedReading) -> u32 {
+        self.timestamp.wrapping_sub(earlier.timestamp)
+    }
+
+    /// Change per second since an earlier reading in this reading's unit,
+    /// [None] if both were taken in the same millisecond
+    pub fn rate_of_change(&self, earlier: &TimestampedReading) -> Option<Reading> {
+        let elapsed = self.elapsed_since(earlier);
+        if elapsed == 0 {
+            return None;
+        }
+
+        Some((self.reading - earlier.reading) / (elapsed as f32 / 1_000f32))
+    }
+}
+
+impl From<TimestampedReading> for LogEntry {
+    fn from(reading: TimestampedReading) -> Self {
+        LogEntry {
+            reading: reading.reading,
+            timestamp: Some(reading.timestamp),
+        }
+    }
+}
+
+/// Stamps readings with the time they were taken at
+#[derive(Clone, Debug)]
+pub struct Timestamped<R, C> {
+    reader: R,
+    clock: C,
+}
+
+impl<R, C> Timestamped<R, C>
+where
+    R: Sht31Reader,
+    C: Clock,
+{
+    pub fn new(reader: R, clock: C) -> Self {
+        Self { reader, clock }
+    }
+
+    /// Reads the sensor, the timestamp is taken once the read is done
+    pub fn read_timestamped(&mut self) -> Result<TimestampedReading> {
+        let reading = self.reader.read()?;
+        Ok(TimestampedReading {
+            reading,
+            timestamp: self.clock.now_ms(),
+        })
+    }
+
+    /// The clock's current time
+    pub fn now_ms(&mut self) -> u32 {
+        self.clock.now_ms()
+    }
+
+    /// Consumes the wrapper and returns the reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R, C> Sht31Reader for Timestamped<R, C>
+where
+    R: Sht31Reader,
+    C: Clock,
+{
+    fn read(&mut self) -> Result<Reading> {
+        self.reader.read()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TemperatureUnit;
+
+    /// Warms up by half a degree every read
+    struct Warming(f32);
+
+    impl Sht31Reader for Warming {
+        fn read(&mut self) -> Result<Reading> {
+            self.0 += 0.5;
+            Ok(Reading {
+                temperature: self.0,
+                humidity: 40f32,
+                unit: TemperatureUnit::Celsius,
+            })
+        }
+    }
+
+    #[test]
+    fn timestamps() {
+        // Wraps around between the reads
+        let mut now = u32::MAX - 100;
+        let clock = || {
+            now = now.wrapping_add(250);
+            now
+        };
+        let mut sensor = Timestamped::new(Warming(20f32), clock);
+
+        let first = sensor.read_timestamped().unwrap();
+        let second = sensor.read_timestamped().unwrap();
+        assert_eq!(first.timestamp, 149);
+        assert_eq!(second.elapsed_since(&first), 250);
+
+        let rate = second.rate_of_change(&first).unwrap();
+        assert_eq!(rate.temperature, 2f32);
+        assert_eq!(rate.humidity, 0f32);
+        assert_eq!(first.rate_of_change(&first), None);
+        assert_eq!(LogEntry::from(second).timestamp, Some(399));
+    }
+}
@@ -0,0 +1,166 @@
+//! Every command the driver sends, for reaching datasheet features the typed API
+//! doesn't cover
+use crate::{clock::MaybeClock, error::Result, mode::MPS, Accuracy, SHT31};
+use embedded_hal::i2c::I2c;
+
+/// A sensor command and its opcode
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Command {
+    /// Single shot measurement, with `clock_stretch` the sensor holds the clock until it's done
+    SingleShot {
+        accuracy: Accuracy,
+        clock_stretch: bool,
+    },
+    /// Starts periodic measurements
+    Periodic {
+        mps: MPS,
+        accuracy: Accuracy,
+    },
+    /// Starts periodic measurements with accelerated response time
+    Art,
+    /// Fetches the latest periodic measurement
+    FetchData,
+    /// Stops periodic measurements
+    BreakCommand,
+    SoftReset,
+    HeaterOn,
+    HeaterOff,
+    ReadStatus,
+    ClearStatus,
+    ReadSerialNumber,
+    /// Any other opcode from the datasheet
+    Custom(u16),
+}
+
+impl Command {
+    /// The opcode as sent over the bus
+    pub fn to_bytes(&self) -> [u8; 2] {
+        let opcode: u16 = match *self {
+            Command::SingleShot {
+                accuracy,
+                clock_stretch: true,
+            } => match accuracy {
+                Accuracy::High => 0x2C06,
+                Accuracy::Medium => 0x2C0D,
+                Accuracy::Low => 0x2C10,
+            },
+            Command::SingleShot {
+                accuracy,
+                clock_stretch: false,
+            } => match accuracy {
+                Accuracy::High => 0x2400,
+                Accuracy::Medium => 0x240B,
+                Accuracy::Low => 0x2416,
+            },
+            Command::Periodic { mps, accuracy } => {
+                let lsb = match mps {
+                    MPS::Half => match accuracy {
+                        Accuracy::High => 0x32,
+                        Accuracy::Medium => 0x24,
+                        Accuracy::Low => 0x2F,
+                    },
+                    MPS::Normal => match accuracy {
+                        Accuracy::High => 0x30,
+                        Accuracy::Medium => 0x26,
+                        Accuracy::Low => 0x2D,
+                    },
+                    MPS::Double => match accuracy {
+                        Accuracy::High => 0x36,
+                        Accuracy::Medium => 0x20,
+                        Accuracy::Low => 0x2B,
+                    },
+                    MPS::X4 => match accuracy {
+                        Accuracy::High => 0x34,
+                        Accuracy::Medium => 0x22,
+                        Accuracy::Low => 0x29,
+                    },
+                    MPS::X10 => match accuracy {
+                        Accuracy::High => 0x37,
+                        Accuracy::Medium => 0x21,
+                        Accuracy::Low => 0x2A,
+                    },
+                };
+                u16::from_be_bytes([mps as u8, lsb])
+            }
+            Command::Art => 0x2B32,
+            Command::FetchData => 0xE000,
+            Command::BreakCommand => 0x3093,
+            Command::SoftReset => 0x30A2,
+            Command::HeaterOn => 0x306D,
+            Command::HeaterOff => 0x3066,
+            Command::ReadStatus => 0xF32D,
+            Command::ClearStatus => 0x3041,
+            Command::ReadSerialNumber => 0x3780,
+            Command::Custom(opcode) => opcode,
+        };
+
+        opcode.to_be_bytes()
+    }
+}
+
+impl<Mode, I2C, C> SHT31<Mode, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Sends a raw command, the driver's state isn't updated to match so e.g. switching
+    /// the heater here won't be reflected in the readings' compensation
+    pub fn send_command(&mut self, command: Command) -> Result<()> {
+        self.i2c_write(&command.to_bytes())
+    }
+
+    /// Sends a raw command and reads its response as is, checksums included but unverified
+    pub fn command_and_read(&mut self, command: Command, buffer: &mut [u8]) -> Result<()> {
+        self.i2c_write_read(&command.to_bytes(), buffer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use super::*;
+    use crate::prelude::*;
+    use alloc::vec;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    #[test]
+    fn opcodes() {
+        assert_eq!(
+            Command::SingleShot {
+                accuracy: Accuracy::Medium,
+                clock_stretch: true
+            }
+            .to_bytes(),
+            [0x2C, 0x0D]
+        );
+        assert_eq!(
+            Command::Periodic {
+                mps: MPS::Half,
+                accuracy: Accuracy::Low
+            }
+            .to_bytes(),
+            [0x20, 0x2F]
+        );
+        assert_eq!(Command::Custom(0xE11F).to_bytes(), [0xE1, 0x1F]);
+    }
+
+    #[test]
+    fn raw_commands() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x30, 0x93]),
+            // Read the high alert limit
+            Transaction::write_read(0x44, vec![0xE1, 0x1F], vec![0xCD, 0x33, 0x00]),
+        ];
+        let mut sensor = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+
+        sensor.send_command(Command::BreakCommand).unwrap();
+        let mut buffer = [0; 3];
+        sensor
+            .command_and_read(Command::Custom(0xE11F), &mut buffer)
+            .unwrap();
+        assert_eq!(buffer, [0xCD, 0x33, 0x00]);
+
+        sensor.destroy().done();
+    }
+}
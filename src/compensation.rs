@@ -0,0 +1,378 @@
+//! Corrections applied to readings before they're handed out
+use crate::{mode::MPS, Reading, TemperatureUnit};
+
+/// Die self-heating model, offsets are in Celsius and subtracted from the temperature
+#[derive(Default, Copy, Clone, Debug)]
+pub struct SelfHeating {
+    /// Offsets for [MPS::Half], [MPS::Normal], [MPS::Double], [MPS::X4] and [MPS::X10]
+    mps: [f32; 5],
+    heater: f32,
+    decay: Option<HeaterDecay>,
+}
+
+/// How the heater offset fades while the die cools down after the heater turned off
+#[derive(Copy, Clone, Debug)]
+struct HeaterDecay {
+    time_constant_ms: u32,
+    heating: bool,
+    /// When the heater last turned off
+    off_since: Option<u32>,
+}
+
+impl SelfHeating {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the offset applied while measuring periodically at the given MPS
+    pub fn with_mps_offset(mut self, mps: MPS, offset: f32) -> Self {
+        self.mps[mps_index(mps)] = offset;
+        self
+    }
+
+    /// Sets the offset applied while the heater is on
+    pub fn with_heater_offset(mut self, offset: f32) -> Self {
+        self.heater = offset;
+        self
+    }
+
+    /// Keeps applying the heater offset after the heater turned off, fading with the
+    /// given time constant as the die cools down. Time comes from the driver's
+    /// [Clock](crate::clock::Clock), without one the offset stops once the heater is off
+    pub fn with_heater_decay(mut self, time_constant_ms: u32) -> Self {
+        self.decay = Some(HeaterDecay {
+            time_constant_ms,
+            heating: false,
+            off_since: None,
+        });
+        self
+    }
+
+    fn heater_changed(&mut self, heating: bool, now: Option<u32>) {
+        if let Some(decay) = &mut self.decay {
+            if heating {
+                decay.off_since = None;
+            } else if decay.heating {
+                decay.off_since = now;
+            }
+            decay.heating = heating;
+        }
+    }
+
+    /// The heater offset left at `now` while the heater is off
+    fn residual_heater_offset(&self, now: Option<u32>) -> f32 {
+        match (self.decay, now) {
+            (
+                Some(HeaterDecay {
+                    time_constant_ms,
+                    off_since: Some(since),
+                    ..
+                }),
+                Some(now),
+            ) => {
+                let elapsed = now.wrapping_sub(since) as f32;
+                self.heater * libm::expf(-elapsed / time_constant_ms as f32)
+            }
+            _ => 0f32,
+        }
+    }
+
+    /// The total offset in Celsius for the given conditions
+    fn offset(&self, heater: bool, mps: Option<MPS>, now: Option<u32>) -> f32 {
+        let heater = if heater {
+            self.heater
+        } else {
+            self.residual_heater_offset(now)
+        };
+        let mps = mps.map_or(0f32, |mps| self.mps[mps_index(mps)]);
+        heater + mps
+    }
+}
+
+fn mps_index(mps: MPS) -> usize {
+    match mps {
+        MPS::Half => 0,
+        MPS::Normal => 1,
+        MPS::Double => 2,
+        MPS::X4 => 3,
+        MPS::X10 => 4,
+    }
+}
+
+/// Converts a Celsius temperature difference into the given unit
+fn delta_in_unit(delta: f32, unit: TemperatureUnit) -> f32 {
+    match unit {
+        TemperatureUnit::Celsius => delta,
+        TemperatureUnit::Fahrenheit => delta * 9f32 / 5f32,
+    }
+}
+
+/// Slope and offset correction of a single channel
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Linear {
+    pub slope: f32,
+    pub offset: f32,
+}
+
+impl Linear {
+    /// Fits the correction through two (measured, reference) points,
+    /// the measured values must differ
+    pub fn from_points(low: (f32, f32), high: (f32, f32)) -> Self {
+        let slope = (high.1 - low.1) / (high.0 - low.0);
+        Self {
+            slope,
+            offset: low.1 - slope * low.0,
+        }
+    }
+
+    fn apply(&self, value: f32) -> f32 {
+        self.slope * value + self.offset
+    }
+}
+
+impl Default for Linear {
+    fn default() -> Self {
+        Self {
+            slope: 1f32,
+            offset: 0f32,
+        }
+    }
+}
+
+/// Per unit calibration against a reference, temperatures are in Celsius and
+/// humidities in %RH
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+pub struct Calibration {
+    pub temperature: Linear,
+    pub humidity: Linear,
+}
+
+impl Calibration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the temperature correction
+    pub fn with_temperature(mut self, temperature: Linear) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// Sets the humidity correction
+    pub fn with_humidity(mut self, humidity: Linear) -> Self {
+        self.humidity = humidity;
+        self
+    }
+
+    fn apply(&self, reading: Reading) -> Reading {
+        let identity = Linear::default();
+        // The round trip through Celsius isn't exact, uncalibrated values are left as they are
+        let temperature = if self.temperature == identity {
+            reading.temperature
+        } else {
+            reading.in_unit(self.temperature.apply(reading.celsius()))
+        };
+        let humidity = if self.humidity == identity {
+            reading.humidity
+        } else {
+            self.humidity.apply(reading.humidity).clamp(0f32, 100f32)
+        };
+
+        Reading {
+            temperature,
+            humidity,
+            unit: reading.unit,
+        }
+    }
+}
+
+/// Every correction configured on a sensor
+#[derive(Default, Copy, Clone, Debug)]
+pub(crate) struct Compensation {
+    pub(crate) self_heating: Option<SelfHeating>,
+    pub(crate) calibration: Calibration,
+}
+
+impl Compensation {
+    /// Notes the heater switching at `now`, so its offset can decay once it's off
+    pub(crate) fn heater_changed(&mut self, heating: bool, now: Option<u32>) {
+        if let Some(self_heating) = &mut self.self_heating {
+            self_heating.heater_changed(heating, now);
+        }
+    }
+
+    /// Corrects the reading given the heater state, the periodic MPS if any and
+    /// the driver's time if it has a clock
+    pub(crate) fn apply(
+        &self,
+        mut reading: Reading,
+        heater: bool,
+        mps: Option<MPS>,
+        now: Option<u32>,
+    ) -> Reading {
+        if let Some(self_heating) = &self.self_heating {
+            let offset = self_heating.offset(heater, mps, now);
+            reading.temperature -= delta_in_unit(offset, reading.unit);
+        }
+
+        self.calibration.apply(reading)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn self_heating() {
+        let compensation = Compensation {
+            self_heating: Some(
+                SelfHeating::new()
+                    .with_mps_offset(MPS::X10, 0.3)
+                    .with_heater_offset(2f32),
+            ),
+            ..Default::default()
+        };
+        let reading = Reading {
+            temperature: 20f32,
+            humidity: 50f32,
+            unit: TemperatureUnit::Celsius,
+        };
+
+        assert_eq!(
+            compensation.apply(reading, false, None, None).temperature,
+            20f32
+        );
+        assert_eq!(
+            compensation
+                .apply(reading, false, Some(MPS::Normal), None)
+                .temperature,
+            20f32
+        );
+        assert_eq!(
+            compensation
+                .apply(reading, true, Some(MPS::X10), None)
+                .temperature,
+            17.7
+        );
+
+        let fahrenheit = Reading {
+            temperature: 68f32,
+            unit: TemperatureUnit::Fahrenheit,
+            ..reading
+        };
+        assert_eq!(
+            compensation.apply(fahrenheit, true, None, None).temperature,
+            64.4
+        );
+    }
+
+    #[test]
+    fn heater_decay() {
+        let mut compensation = Compensation {
+            self_heating: Some(
+                SelfHeating::new()
+                    .with_heater_offset(2f32)
+                    .with_heater_decay(1000),
+            ),
+            ..Default::default()
+        };
+        let reading = Reading {
+            temperature: 20f32,
+            humidity: 50f32,
+            unit: TemperatureUnit::Celsius,
+        };
+
+        // Never heated, e.g. a reset with the heater already off
+        compensation.heater_changed(false, Some(0));
+        assert_eq!(
+            compensation
+                .apply(reading, false, None, Some(0))
+                .temperature,
+            20f32
+        );
+
+        compensation.heater_changed(true, Some(0));
+        compensation.heater_changed(false, Some(5000));
+        // Still as warm as the moment the heater turned off
+        assert_eq!(
+            compensation
+                .apply(reading, false, None, Some(5000))
+                .temperature,
+            18f32
+        );
+
+        let temperature = compensation
+            .apply(reading, false, None, Some(6000))
+            .temperature;
+        assert!((temperature - (20f32 - 2f32 / core::f32::consts::E)).abs() < 0.001);
+    }
+
+    #[test]
+    fn offsets() {
+        let compensation = Compensation {
+            calibration: Calibration::new()
+                .with_temperature(Linear {
+                    slope: 1f32,
+                    offset: -0.5,
+                })
+                .with_humidity(Linear {
+                    slope: 1f32,
+                    offset: 2f32,
+                }),
+            ..Default::default()
+        };
+        let reading = Reading {
+            temperature: 68f32,
+            humidity: 99f32,
+            unit: TemperatureUnit::Fahrenheit,
+        };
+
+        let compensated = compensation.apply(reading, false, None, None);
+        assert!((compensated.temperature - 67.1).abs() < 0.001);
+        assert_eq!(compensated.humidity, 100f32);
+    }
+
+    #[test]
+    fn identity() {
+        // 60.86 °F doesn't survive a round trip through Celsius
+        let reading = Reading {
+            temperature: 60.86,
+            humidity: 50.3,
+            unit: TemperatureUnit::Fahrenheit,
+        };
+        let compensation = Compensation::default();
+        assert_eq!(compensation.apply(reading, false, None, None), reading);
+
+        let compensation = Compensation {
+            calibration: Calibration::new().with_humidity(Linear {
+                slope: 1f32,
+                offset: 1f32,
+            }),
+            ..Default::default()
+        };
+        let compensated = compensation.apply(reading, false, None, None);
+        assert_eq!(compensated.temperature, reading.temperature);
+        assert_eq!(compensated.humidity, 51.3);
+    }
+
+    #[test]
+    fn two_point() {
+        let humidity = Linear::from_points((11f32, 11.3), (75f32, 75.5));
+        assert!((humidity.slope - 1.003125).abs() < 0.0001);
+        assert!((humidity.apply(11f32) - 11.3).abs() < 0.001);
+        assert!((humidity.apply(75f32) - 75.5).abs() < 0.001);
+
+        let compensation = Compensation {
+            calibration: Calibration::new()
+                .with_temperature(Linear::from_points((0f32, 0.2), (50f32, 50.7))),
+            ..Default::default()
+        };
+        let reading = Reading {
+            temperature: 25f32,
+            humidity: 50f32,
+            unit: TemperatureUnit::Celsius,
+        };
+        assert!((compensation.apply(reading, false, None, None).temperature - 25.45).abs() < 0.001);
+    }
+}
@@ -0,0 +1,156 @@
+//! Quantities derived from a temperature and relative humidity [Reading]
+use crate::{Reading, TemperatureUnit};
+
+/// Standard sea level atmospheric pressure in hPa
+pub const STANDARD_PRESSURE: f32 = 1013.25;
+
+impl Reading {
+    /// The reading's temperature in Celsius
+    pub(crate) fn celsius(&self) -> f32 {
+        match self.unit {
+            TemperatureUnit::Celsius => self.temperature,
+            TemperatureUnit::Fahrenheit => (self.temperature - 32f32) * 5f32 / 9f32,
+        }
+    }
+
+    /// Converts a Celsius temperature into the reading's unit
+    pub(crate) fn in_unit(&self, celsius: f32) -> f32 {
+        match self.unit {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => celsius * 9f32 / 5f32 + 32f32,
+        }
+    }
+
+    /// Actual water vapor pressure in hPa
+    fn vapor_pressure(&self) -> f32 {
+        saturation_vapor_pressure(self.celsius()) * self.humidity / 100f32
+    }
+
+    /// The Canadian humidex, expressed in the reading's temperature unit
+    pub fn humidex(&self) -> f32 {
+        let humidex = self.celsius() + 0.5555 * (self.vapor_pressure() - 10f32);
+        self.in_unit(humidex)
+    }
+
+    /// Wet-bulb temperature using the Stull (2011) approximation, expressed in the
+    /// reading's temperature unit. Valid between 5% and 99% RH and -20°C to 50°C
+    pub fn wet_bulb(&self) -> f32 {
+        let t = self.celsius();
+        let rh = self.humidity;
+
+        let wet_bulb = t * libm::atanf(0.151977 * libm::sqrtf(rh + 8.313659)) + libm::atanf(t + rh)
+            - libm::atanf(rh - 1.676331)
+            + 0.00391838 * libm::powf(rh, 1.5) * libm::atanf(0.023101 * rh)
+            - 4.686035;
+        self.in_unit(wet_bulb)
+    }
+
+    /// Vapor pressure deficit in kPa
+    pub fn vpd(&self) -> f32 {
+        let saturation = saturation_vapor_pressure(self.celsius());
+        (saturation - self.vapor_pressure()) / 10f32
+    }
+
+    /// Mass of water vapor per mass of dry air in kg/kg at the given pressure in hPa
+    fn humidity_ratio(&self, pressure: f32) -> f32 {
+        let vapor_pressure = self.vapor_pressure();
+        0.622 * vapor_pressure / (pressure - vapor_pressure)
+    }
+
+    /// Specific enthalpy of moist air in kJ/kg of dry air at standard sea level pressure
+    pub fn enthalpy(&self) -> f32 {
+        self.enthalpy_at(STANDARD_PRESSURE)
+    }
+
+    /// Specific enthalpy of moist air in kJ/kg of dry air at the given pressure in hPa
+    pub fn enthalpy_at(&self, pressure: f32) -> f32 {
+        let t = self.celsius();
+        1.006 * t + self.humidity_ratio(pressure) * (2501f32 + 1.86 * t)
+    }
+
+    /// Mixing ratio in grams of water vapor per kg of dry air at standard sea level pressure
+    pub fn mixing_ratio(&self) -> f32 {
+        self.mixing_ratio_at(STANDARD_PRESSURE)
+    }
+
+    /// Mixing ratio in grams of water vapor per kg of dry air at the given pressure in hPa
+    pub fn mixing_ratio_at(&self, pressure: f32) -> f32 {
+        self.humidity_ratio(pressure) * 1000f32
+    }
+
+    /// Specific humidity in grams of water vapor per kg of moist air at standard sea level pressure
+    pub fn specific_humidity(&self) -> f32 {
+        self.specific_humidity_at(STANDARD_PRESSURE)
+    }
+
+    /// Specific humidity in grams of water vapor per kg of moist air at the given pressure in hPa
+    pub fn specific_humidity_at(&self, pressure: f32) -> f32 {
+        let ratio = self.humidity_ratio(pressure);
+        ratio / (1f32 + ratio) * 1000f32
+    }
+}
+
+/// Saturation vapor pressure in hPa over water at the given Celsius temperature (Bolton, 1980)
+fn saturation_vapor_pressure(celsius: f32) -> f32 {
+    6.112 * libm::expf(17.67 * celsius / (celsius + 243.5))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Reading, TemperatureUnit};
+
+    fn reading(temperature: f32, humidity: f32, unit: TemperatureUnit) -> Reading {
+        Reading {
+            temperature,
+            humidity,
+            unit,
+        }
+    }
+
+    #[test]
+    fn humidex() {
+        let celsius = reading(30f32, 70f32, TemperatureUnit::Celsius);
+        assert!((celsius.humidex() - 40.95).abs() < 0.1);
+
+        let fahrenheit = reading(86f32, 70f32, TemperatureUnit::Fahrenheit);
+        assert!((fahrenheit.humidex() - 105.71).abs() < 0.2);
+    }
+
+    #[test]
+    fn wet_bulb() {
+        let celsius = reading(20f32, 50f32, TemperatureUnit::Celsius);
+        assert!((celsius.wet_bulb() - 13.7).abs() < 0.1);
+
+        let fahrenheit = reading(68f32, 50f32, TemperatureUnit::Fahrenheit);
+        assert!((fahrenheit.wet_bulb() - 56.66).abs() < 0.2);
+    }
+
+    #[test]
+    fn vpd() {
+        let celsius = reading(25f32, 60f32, TemperatureUnit::Celsius);
+        assert!((celsius.vpd() - 1.267).abs() < 0.01);
+
+        let saturated = reading(77f32, 100f32, TemperatureUnit::Fahrenheit);
+        assert!(saturated.vpd().abs() < 0.001);
+    }
+
+    #[test]
+    fn enthalpy() {
+        let celsius = reading(25f32, 50f32, TemperatureUnit::Celsius);
+        assert!((celsius.enthalpy() - 50.3).abs() < 0.1);
+
+        let fahrenheit = reading(77f32, 50f32, TemperatureUnit::Fahrenheit);
+        assert!((fahrenheit.enthalpy() - celsius.enthalpy()).abs() < 0.01);
+
+        // Lower pressure means more vapor per kg of dry air
+        assert!(celsius.enthalpy_at(850f32) > celsius.enthalpy());
+    }
+
+    #[test]
+    fn mixing_ratio() {
+        let celsius = reading(25f32, 50f32, TemperatureUnit::Celsius);
+        assert!((celsius.mixing_ratio() - 9.88).abs() < 0.05);
+        assert!((celsius.specific_humidity() - 9.78).abs() < 0.05);
+        assert!(celsius.specific_humidity() < celsius.mixing_ratio());
+    }
+}
@@ -0,0 +1,129 @@
+//! Typed `fugit` durations for the parameters that otherwise take raw milliseconds,
+//! any unit converts so e.g. `2.secs()` and `2_000.millis()` are interchangeable
+use crate::{
+    clock::MaybeClock,
+    error::Result,
+    filter::ThermalLag,
+    heater::HeaterGuard,
+    mode::{Backoff, Measuring, Periodic, Sht31Reader, SimpleSingleShot, MPS},
+    Accuracy, Reading, SHT31,
+};
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+use fugit::{Duration, MicrosDurationU32, MillisDurationU32};
+
+impl Accuracy {
+    /// Maximum time a measurement takes at this accuracy
+    pub fn max_duration(&self) -> MicrosDurationU32 {
+        MicrosDurationU32::micros(self.max_duration_us())
+    }
+}
+
+impl MPS {
+    /// Time between two measurements
+    pub fn period(&self) -> MillisDurationU32 {
+        MillisDurationU32::millis(self.period_ms())
+    }
+}
+
+impl<State> Periodic<State> {
+    /// Time between two measurements at the effective MPS
+    pub fn period(&self) -> MillisDurationU32 {
+        MillisDurationU32::millis(self.period_ms())
+    }
+}
+
+impl Backoff {
+    /// How long to wait before the given retry, starting at 0
+    pub fn delay(&self, retry: u8) -> MillisDurationU32 {
+        MillisDurationU32::millis(self.delay_ms(retry))
+    }
+}
+
+impl<const NOM: u32, const DENOM: u32> From<Duration<u32, NOM, DENOM>> for Backoff {
+    /// Always waits the same duration
+    fn from(duration: Duration<u32, NOM, DENOM>) -> Self {
+        Backoff::Fixed(duration.to_millis())
+    }
+}
+
+impl<D, Y> SimpleSingleShot<D, Y>
+where
+    D: DelayNs,
+    Y: FnMut(u32),
+{
+    /// Sets the delay between each try
+    pub fn set_delay_duration<const NOM: u32, const DENOM: u32>(
+        &mut self,
+        delay: Duration<u32, NOM, DENOM>,
+    ) {
+        self.set_backoff(delay.into())
+    }
+
+    /// Sets the delay between each try
+    pub fn with_delay_duration<const NOM: u32, const DENOM: u32>(
+        mut self,
+        delay: Duration<u32, NOM, DENOM>,
+    ) -> Self {
+        self.set_delay_duration(delay);
+        self
+    }
+}
+
+impl<I2C, C> SHT31<Periodic<Measuring>, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// [SHT31::fetch_latest_or_wait] with a typed timeout
+    pub fn fetch_latest_or_wait_for<D: DelayNs, const NOM: u32, const DENOM: u32>(
+        &mut self,
+        delay: &mut D,
+        timeout: Duration<u32, NOM, DENOM>,
+    ) -> Result<Reading> {
+        self.fetch_latest_or_wait(delay, timeout.to_millis())
+    }
+}
+
+impl HeaterGuard {
+    /// [HeaterGuard::new] with typed durations, the driver's clock still counts milliseconds
+    pub fn from_durations<const NOM: u32, const DENOM: u32>(
+        max_on: Duration<u32, NOM, DENOM>,
+        min_off: Duration<u32, NOM, DENOM>,
+    ) -> Self {
+        Self::new(max_on.to_millis(), min_off.to_millis())
+    }
+}
+
+impl<R> ThermalLag<R>
+where
+    R: Sht31Reader,
+{
+    /// [ThermalLag::new] with typed durations
+    pub fn from_durations<const NOM: u32, const DENOM: u32>(
+        reader: R,
+        time_constant: Duration<u32, NOM, DENOM>,
+        interval: Duration<u32, NOM, DENOM>,
+    ) -> Self {
+        Self::new(reader, time_constant.to_millis(), interval.to_millis())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+    use fugit::ExtU32;
+
+    #[test]
+    fn durations() {
+        assert_eq!(Accuracy::Low.max_duration(), 4_500.micros::<1, 1_000_000>());
+        assert_eq!(MPS::Half.period(), 2.secs::<1, 1>());
+        assert_eq!(
+            Periodic::new().with_art().period(),
+            250.millis::<1, 1_000>()
+        );
+        assert_eq!(
+            Backoff::from(2.secs::<1, 1>()).delay(3),
+            2_000.millis::<1, 1_000>()
+        );
+    }
+}
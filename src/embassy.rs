@@ -0,0 +1,182 @@
+//! Async helpers for embassy, the sensor is fetched at its own rate and the readings are
+//! published for other tasks. Embassy tasks can't be generic so these are meant to be
+//! awaited from a task of the application's own.
+//!
+//! Only the waiting is async, the bus is a blocking [I2c] and every fetch blocks the
+//! executor for its transfer. A fetch is a single short `write_read` without clock
+//! stretching, still on a slow or contended bus other tasks wait it out
+use crate::{
+    clock::{MaybeClock, NoClock},
+    error::Result,
+    mode::{
+        periodic::{FreshFetch, FreshPoll},
+        Measuring, Periodic,
+    },
+    Reading, SHT31,
+};
+use embassy_sync::{blocking_mutex::raw::RawMutex, channel, watch};
+use embassy_time::{Duration, Ticker, Timer};
+use embedded_hal::i2c::I2c;
+
+/// Fetches periodic measurements on a [Ticker] at the sensor's sample rate, the ticker
+/// keeps to the sensor's schedule without drifting and catches up on ticks that were
+/// missed, so fetches neither run ahead of the sensor nor skip samples
+pub struct FetchScheduler<I2C, C = NoClock> {
+    sensor: SHT31<Periodic<Measuring>, I2C, C>,
+    period: Duration,
+    ticker: Ticker,
+}
+
+impl<I2C, C> FetchScheduler<I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Schedules fetches at the effective MPS, which is 4 Hz with ART, starting a period
+    /// from now so the sensor has been measuring for at least that long
+    pub fn new(sensor: SHT31<Periodic<Measuring>, I2C, C>) -> Self {
+        let period = Duration::from_micros(sensor.sample_period_us() as u64);
+
+        Self {
+            sensor,
+            period,
+            ticker: Ticker::every(period),
+        }
+    }
+
+    /// Time between two fetches
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Waits for the next tick and fetches the fresh measurement, polling for up to half a
+    /// period before giving up with [SHTError::FetchTimeoutError](crate::SHTError::FetchTimeoutError).
+    /// The fetches block the executor, see the [module](self) docs
+    pub async fn next(&mut self) -> Result<Reading> {
+        self.ticker.next().await;
+
+        let mut poll = FreshPoll::new((self.period / 2).as_millis() as u32);
+        loop {
+            match self.sensor.poll_fresh(&mut poll) {
+                FreshFetch::Done(result) => return result,
+                FreshFetch::Retry(ms) => Timer::after_millis(ms as u64).await,
+            }
+        }
+    }
+
+    /// Restarts the schedule a period from now, e.g. after the sensor was re-armed
+    pub fn reset(&mut self) {
+        self.ticker.reset();
+    }
+
+    pub fn sensor_mut(&mut self) -> &mut SHT31<Periodic<Measuring>, I2C, C> {
+        &mut self.sensor
+    }
+
+    pub fn into_inner(self) -> SHT31<Periodic<Measuring>, I2C, C> {
+        self.sensor
+    }
+}
+
+/// Fetches every measurement and sends it into a channel, waiting for room when it's full
+pub async fn publish_to_channel<I2C, C, M, const N: usize>(
+    sensor: SHT31<Periodic<Measuring>, I2C, C>,
+    sender: channel::Sender<'_, M, Result<Reading>, N>,
+) -> !
+where
+    I2C: I2c,
+    C: MaybeClock,
+    M: RawMutex,
+{
+    let mut scheduler = FetchScheduler::new(sensor);
+    loop {
+        let reading = scheduler.next().await;
+        sender.send(reading).await;
+    }
+}
+
+/// Fetches every measurement and publishes it to a watch, where receivers only see the latest
+pub async fn publish_to_watch<I2C, C, M, const N: usize>(
+    sensor: SHT31<Periodic<Measuring>, I2C, C>,
+    sender: watch::Sender<'_, M, Result<Reading>, N>,
+) -> !
+where
+    I2C: I2c,
+    C: MaybeClock,
+    M: RawMutex,
+{
+    let mut scheduler = FetchScheduler::new(sensor);
+    loop {
+        let reading = scheduler.next().await;
+        sender.send(reading);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use super::*;
+    use crate::prelude::*;
+    use alloc::vec;
+    use embassy_sync::{blocking_mutex::raw::NoopRawMutex, channel::Channel};
+    use embassy_time::Instant;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    use futures::{
+        executor::block_on,
+        future::{select, Either},
+        pin_mut,
+    };
+
+    #[test]
+    fn channel() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x27, 0x37]),
+            Transaction::write(0x44, vec![0xE0, 0x00]),
+            Transaction::read(0x44, vec![0; 6])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            Transaction::write(0x44, vec![0xE0, 0x00]),
+            Transaction::read(0x44, vec![98, 153, 188, 98, 32, 139]),
+        ];
+        let mut i2c = Mock::new(&expectations);
+        let sensor = SHT31::periodic(i2c.clone(), Periodic::new().with_mps(MPS::X10))
+            .measure()
+            .unwrap();
+
+        let channel = Channel::<NoopRawMutex, Result<Reading>, 1>::new();
+        let publisher = publish_to_channel(sensor, channel.sender());
+        let receiver = channel.receive();
+        pin_mut!(publisher, receiver);
+
+        // The not ready fetch is polled again instead of published
+        let reading = match block_on(select(publisher, receiver)) {
+            Either::Right((reading, _)) => reading.unwrap(),
+        };
+        assert_eq!(reading.temperature, 72.32318);
+
+        i2c.done();
+    }
+
+    #[test]
+    fn scheduler() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x2B, 0x32]),
+            Transaction::write(0x44, vec![0xE0, 0x00]),
+            Transaction::read(0x44, vec![98, 153, 188, 98, 32, 139]),
+        ];
+        let mut i2c = Mock::new(&expectations);
+        let sensor = SHT31::periodic(i2c.clone(), Periodic::new().with_art())
+            .measure()
+            .unwrap();
+
+        let mut scheduler = FetchScheduler::new(sensor);
+        assert_eq!(scheduler.period(), Duration::from_millis(250));
+
+        let start = Instant::now();
+        let reading = block_on(scheduler.next()).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(250));
+        assert_eq!(reading.humidity, 38.33066);
+
+        i2c.done();
+    }
+}
@@ -1,10 +1,15 @@
 use thiserror::Error;
 
 pub type Result<T> = core::result::Result<T, SHTError>;
+/// Result of a typestate transition, the previous sensor is handed back on failure
+pub type TransitionResult<Next, Previous> = core::result::Result<Next, TransitionError<Previous>>;
 #[derive(Error, Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum SHTError {
     #[error("Read I2C Error")]
     ReadI2CError,
+    #[error("Sensor NACKed the read, no data is ready yet")]
+    DataNotReadyError,
     #[error("Write Read I2C Error")]
     WriteReadI2CError,
     #[error("Write I2C Error")]
@@ -30,8 +35,68 @@ pub enum SHTError {
         expected_checksum: u8,
         calculated_checksum: u8,
     },
-    #[error("Single shot reading timeout")]
-    ReadingTimeoutError,
-    #[error("This error should not happen")]
-    PlaceholderError,
+    #[error("No sensor responded on either address")]
+    DeviceNotFoundError,
+    #[error("Redundant sensors disagree on temperature beyond the tolerance")]
+    TemperatureDivergenceError,
+    #[error("Redundant sensors disagree on humidity beyond the tolerance")]
+    HumidityDivergenceError,
+    #[error("Serial number bytes [{bytes_start:#x}, {bytes_end:#x}] expected {expected_checksum:#x} but got the checksum {calculated_checksum:#x}")]
+    InvalidSerialNumberChecksumError {
+        bytes_start: u8,
+        bytes_end: u8,
+        expected_checksum: u8,
+        calculated_checksum: u8,
+    },
+    #[error("Heater state doesn't match the status register")]
+    HeaterStateError,
+    #[error("Heater exceeded its maximum on time")]
+    HeaterOnTimeError,
+    #[error("Heater hasn't been off for its minimum off time")]
+    HeaterCooldownError,
+    #[error("Pin Error")]
+    PinError,
+    #[error("SDA is still held low after clocking SCL")]
+    BusStuckError,
+    #[error("Custom addresses can't be selected through the ADDR pin")]
+    UnsupportedAddressError,
+    #[error("Command wasn't processed in time")]
+    CommandTimeoutError,
+    #[error("No fresh periodic data arrived in time")]
+    FetchTimeoutError,
+    #[error("Reading is physically impossible")]
+    ImplausibleReadingError,
+    #[error("Sensor keeps returning identical readings and should be reset")]
+    SensorStuckError,
+    #[error("Single shot reading timeout after {attempts} attempts")]
+    ReadingTimeoutError { attempts: u8 },
+    #[error("Buffer is too small for the formatted output")]
+    BufferTooSmallError,
+    #[error("No supported measurement frequency matches the requested one")]
+    UnsupportedFrequencyError,
+}
+
+/// Returned when a typestate transition fails so the sensor isn't lost along with the error
+#[derive(Error, Debug)]
+#[error("{error}")]
+pub struct TransitionError<S> {
+    /// The sensor in its previous state
+    pub sensor: S,
+    pub error: SHTError,
+}
+
+impl<S> TransitionError<S> {
+    /// Wraps the handed back sensor
+    pub(crate) fn map_sensor<T>(self, f: impl FnOnce(S) -> T) -> TransitionError<T> {
+        TransitionError {
+            sensor: f(self.sensor),
+            error: self.error,
+        }
+    }
+}
+
+impl<S> From<TransitionError<S>> for SHTError {
+    fn from(err: TransitionError<S>) -> Self {
+        err.error
+    }
 }
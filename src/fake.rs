@@ -0,0 +1,129 @@
+//! A stand-in sensor for testing application logic without mocking the bus protocol
+use crate::{
+    error::{Result, SHTError, TransitionResult},
+    mode::{Idle, Measuring, Sht31Measure, Sht31Reader},
+    Reading,
+};
+use core::marker::PhantomData;
+
+/// Where the fake sensor's readings come from
+#[derive(Copy, Clone, Debug)]
+pub enum FakeSource<'a> {
+    /// Always the same reading
+    Constant(Reading),
+    /// The results in order, the last one repeats once the script runs out
+    Script(&'a [Result<Reading>]),
+    /// Generated from the number of reads so far, starting at 0
+    Generator(fn(u32) -> Result<Reading>),
+}
+
+/// Implements [Sht31Reader] and [Sht31Measure] like a single shot sensor would,
+/// so it can stand in for the real one wherever those traits are taken
+#[derive(Copy, Clone, Debug)]
+pub struct FakeSht31<'a, State = Idle> {
+    source: FakeSource<'a>,
+    reads: u32,
+    state: PhantomData<State>,
+}
+
+impl<'a> FakeSht31<'a> {
+    pub fn new(source: FakeSource<'a>) -> Self {
+        Self {
+            source,
+            reads: 0,
+            state: PhantomData,
+        }
+    }
+
+    pub fn constant(reading: Reading) -> Self {
+        Self::new(FakeSource::Constant(reading))
+    }
+
+    pub fn script(script: &'a [Result<Reading>]) -> Self {
+        Self::new(FakeSource::Script(script))
+    }
+
+    pub fn generator(generator: fn(u32) -> Result<Reading>) -> Self {
+        Self::new(FakeSource::Generator(generator))
+    }
+}
+
+impl<State> FakeSht31<'_, State> {
+    /// Amount of readings taken so far
+    pub fn reads(&self) -> u32 {
+        self.reads
+    }
+}
+
+impl<'a> Sht31Measure for FakeSht31<'a> {
+    type Measuring = FakeSht31<'a, Measuring>;
+
+    fn measure(self) -> TransitionResult<Self::Measuring, Self> {
+        Ok(FakeSht31 {
+            source: self.source,
+            reads: self.reads,
+            state: PhantomData,
+        })
+    }
+}
+
+impl Sht31Reader for FakeSht31<'_, Measuring> {
+    /// The next reading from the source, an empty script is [SHTError::DataNotReadyError]
+    fn read(&mut self) -> Result<Reading> {
+        let reading = match self.source {
+            FakeSource::Constant(reading) => Ok(reading),
+            FakeSource::Script(script) => {
+                let index = (self.reads as usize).min(script.len().saturating_sub(1));
+                script
+                    .get(index)
+                    .copied()
+                    .unwrap_or(Err(SHTError::DataNotReadyError))
+            }
+            FakeSource::Generator(generator) => generator(self.reads),
+        };
+
+        self.reads = self.reads.wrapping_add(1);
+        reading
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TemperatureUnit;
+
+    fn celsius(temperature: f32) -> Reading {
+        Reading {
+            temperature,
+            humidity: 40f32,
+            unit: TemperatureUnit::Celsius,
+        }
+    }
+
+    #[test]
+    fn script() {
+        let script = [
+            Ok(celsius(20f32)),
+            Err(SHTError::ReadI2CError),
+            Ok(celsius(21f32)),
+        ];
+        let mut sensor = FakeSht31::script(&script).measure().unwrap();
+
+        assert_eq!(sensor.read().unwrap().temperature, 20f32);
+        assert_eq!(sensor.read().unwrap_err(), SHTError::ReadI2CError);
+        assert_eq!(sensor.read().unwrap().temperature, 21f32);
+        // The last reading repeats
+        assert_eq!(sensor.read().unwrap().temperature, 21f32);
+        assert_eq!(sensor.reads(), 4);
+    }
+
+    #[test]
+    fn generator() {
+        let mut sensor = FakeSht31::generator(|read| Ok(celsius(read as f32 / 2f32)))
+            .measure()
+            .unwrap();
+
+        let temperatures = [(); 3].map(|_| sensor.read().unwrap().temperature);
+        assert_eq!(temperatures, [0f32, 0.5, 1f32]);
+    }
+}
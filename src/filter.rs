@@ -0,0 +1,300 @@
+//! Filters layered on top of any [Sht31Reader], a `&mut` reader can be wrapped
+//! to keep ownership of the sensor
+use crate::{error::Result, mode::Sht31Reader, Reading};
+use heapless::HistoryBuffer;
+
+/// How a [Filtered] window is reduced into a reading
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Window {
+    /// Moving average, smooths out noise
+    #[default]
+    Mean,
+    /// Rejects single sample glitches without the lag of a long average
+    Median,
+}
+
+/// Smooths readings over a window of the last `N` readings,
+/// until the window fills up it covers the readings taken so far
+#[derive(Clone, Debug)]
+pub struct Filtered<R, const N: usize> {
+    reader: R,
+    window: HistoryBuffer<Reading, N>,
+    kind: Window,
+}
+
+impl<R, const N: usize> Filtered<R, N>
+where
+    R: Sht31Reader,
+{
+    /// A moving average filter, the window must hold at least one reading
+    pub fn new(reader: R) -> Self {
+        const { assert!(N > 0, "the filter window can't be empty") };
+
+        Self {
+            reader,
+            window: HistoryBuffer::new(),
+            kind: Window::Mean,
+        }
+    }
+
+    /// A median filter
+    pub fn median(reader: R) -> Self {
+        Self::new(reader).with_window(Window::Median)
+    }
+
+    /// Change how the window is reduced
+    pub fn with_window(mut self, kind: Window) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Empty the window
+    pub fn reset(&mut self) {
+        self.window.clear();
+    }
+
+    /// Consumes the filter and returns the reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R, const N: usize> Sht31Reader for Filtered<R, N>
+where
+    R: Sht31Reader,
+{
+    fn read(&mut self) -> Result<Reading> {
+        let reading = self.reader.read()?;
+        self.window.write(reading);
+
+        let (temperature, humidity) = match self.kind {
+            Window::Mean => (self.mean(|r| r.temperature), self.mean(|r| r.humidity)),
+            Window::Median => (
+                self.median_of(|r| r.temperature),
+                self.median_of(|r| r.humidity),
+            ),
+        };
+
+        Ok(Reading {
+            temperature,
+            humidity,
+            unit: reading.unit,
+        })
+    }
+}
+
+impl<R, const N: usize> Filtered<R, N> {
+    fn mean(&self, channel: impl Fn(&Reading) -> f32) -> f32 {
+        self.window.iter().map(channel).sum::<f32>() / self.window.len() as f32
+    }
+
+    fn median_of(&self, channel: impl Fn(&Reading) -> f32) -> f32 {
+        let mut values = [0f32; N];
+        let len = self.window.len();
+        for (value, reading) in values.iter_mut().zip(self.window.iter()) {
+            *value = channel(reading);
+        }
+
+        let values = &mut values[..len];
+        values.sort_unstable_by(f32::total_cmp);
+        if len.is_multiple_of(2) {
+            (values[len / 2 - 1] + values[len / 2]) / 2f32
+        } else {
+            values[len / 2]
+        }
+    }
+}
+
+/// Exponential moving average, an O(1) memory alternative to [Filtered].
+/// The first reading is passed through as is
+#[derive(Copy, Clone, Debug)]
+pub struct Ema<R> {
+    reader: R,
+    alpha: f32,
+    last: Option<Reading>,
+}
+
+impl<R> Ema<R>
+where
+    R: Sht31Reader,
+{
+    /// Wraps a reader given the weight of each new reading between 0 and 1,
+    /// the lower the alpha the smoother the output
+    pub fn new(reader: R, alpha: f32) -> Self {
+        Self {
+            reader,
+            alpha: alpha.clamp(0f32, 1f32),
+            last: None,
+        }
+    }
+
+    /// Forget the average, the next reading is passed through as is
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    /// Consumes the filter and returns the reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R> Sht31Reader for Ema<R>
+where
+    R: Sht31Reader,
+{
+    fn read(&mut self) -> Result<Reading> {
+        let mut reading = self.reader.read()?;
+
+        if let Some(last) = self.last {
+            let blend = |new: f32, old: f32| self.alpha * new + (1f32 - self.alpha) * old;
+            reading.temperature = blend(reading.temperature, last.temperature);
+            reading.humidity = blend(reading.humidity, last.humidity);
+        }
+        self.last = Some(reading);
+
+        Ok(reading)
+    }
+}
+
+/// Estimates the ambient temperature of a sensor in a sealed enclosure that lags behind it,
+/// modeling the enclosure as a first-order system: `ambient = measured + tau * dT/dt`.
+/// Humidity is passed through untouched
+#[derive(Copy, Clone, Debug)]
+pub struct ThermalLag<R> {
+    reader: R,
+    /// Enclosure time constant over the interval between readings
+    ratio: f32,
+    last: Option<f32>,
+}
+
+impl<R> ThermalLag<R>
+where
+    R: Sht31Reader,
+{
+    /// Wraps a reader given the enclosure's time constant and the interval between readings,
+    /// both in milliseconds. A zero interval is treated as one millisecond
+    pub fn new(reader: R, time_constant_ms: u32, interval_ms: u32) -> Self {
+        Self {
+            reader,
+            ratio: time_constant_ms as f32 / interval_ms.max(1) as f32,
+            last: None,
+        }
+    }
+
+    /// Forget the previous reading, the next one is passed through uncompensated
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    /// Consumes the filter and returns the reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R> Sht31Reader for ThermalLag<R>
+where
+    R: Sht31Reader,
+{
+    fn read(&mut self) -> Result<Reading> {
+        let mut reading = self.reader.read()?;
+
+        let measured = reading.temperature;
+        if let Some(last) = self.last {
+            reading.temperature = measured + self.ratio * (measured - last);
+        }
+        self.last = Some(measured);
+
+        Ok(reading)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TemperatureUnit;
+
+    /// Replays the given temperatures
+    struct Replay<'a>(&'a [f32]);
+
+    impl Sht31Reader for Replay<'_> {
+        fn read(&mut self) -> Result<Reading> {
+            let (temperature, rest) = self.0.split_first().unwrap();
+            self.0 = rest;
+            Ok(Reading {
+                temperature: *temperature,
+                humidity: *temperature * 2f32,
+                unit: TemperatureUnit::Celsius,
+            })
+        }
+    }
+
+    #[test]
+    fn moving_average() {
+        let mut filter = Filtered::<_, 3>::new(Replay(&[20f32, 23f32, 26f32, 29f32]));
+
+        assert_eq!(filter.read().unwrap().temperature, 20f32);
+        assert_eq!(filter.read().unwrap().temperature, 21.5);
+        assert_eq!(filter.read().unwrap().temperature, 23f32);
+
+        let reading = filter.read().unwrap();
+        assert_eq!(reading.temperature, 26f32);
+        assert_eq!(reading.humidity, 52f32);
+    }
+
+    #[test]
+    fn median() {
+        let mut filter = Filtered::<_, 3>::median(Replay(&[20f32, 22f32, 80f32, 21f32]));
+
+        assert_eq!(filter.read().unwrap().temperature, 20f32);
+        assert_eq!(filter.read().unwrap().temperature, 21f32);
+        // The glitch is rejected
+        assert_eq!(filter.read().unwrap().temperature, 22f32);
+
+        let reading = filter.read().unwrap();
+        assert_eq!(reading.temperature, 22f32);
+        assert_eq!(reading.humidity, 44f32);
+    }
+
+    #[test]
+    fn ema() {
+        let mut filter = Ema::new(Replay(&[20f32, 24f32, 24f32]), 0.25);
+
+        assert_eq!(filter.read().unwrap().temperature, 20f32);
+        assert_eq!(filter.read().unwrap().temperature, 21f32);
+
+        let reading = filter.read().unwrap();
+        assert_eq!(reading.temperature, 21.75);
+        assert_eq!(reading.humidity, 43.5);
+    }
+
+    #[test]
+    fn thermal_lag() {
+        let mut filter = ThermalLag::new(Replay(&[20f32, 21f32, 21.5, 21.5]), 60_000, 30_000);
+
+        assert_eq!(filter.read().unwrap().temperature, 20f32);
+        assert_eq!(filter.read().unwrap().temperature, 23f32);
+        assert_eq!(filter.read().unwrap().temperature, 22.5);
+        assert_eq!(filter.read().unwrap().temperature, 21.5);
+    }
+
+    #[test]
+    fn thermal_lag_zero_interval() {
+        let mut filter = ThermalLag::new(Replay(&[20f32, 21f32]), 2, 0);
+
+        filter.read().unwrap();
+        assert_eq!(filter.read().unwrap().temperature, 23f32);
+    }
+
+    #[test]
+    fn borrowed_reader() {
+        let mut replay = Replay(&[20f32, 21f32, 22f32]);
+
+        let mut filter = ThermalLag::new(&mut replay, 30_000, 30_000);
+        filter.read().unwrap();
+        assert_eq!(filter.read().unwrap().temperature, 22f32);
+
+        assert_eq!(replay.read().unwrap().temperature, 22f32);
+    }
+}
@@ -0,0 +1,225 @@
+//! Text formatting of readings into caller provided buffers, for targets without an allocator
+use crate::{
+    error::{Result, SHTError},
+    logger::LogEntry,
+    Reading, TemperatureUnit,
+};
+use core::fmt::Write;
+
+/// Writes into a byte buffer, failing once it's full
+struct BufWriter<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> BufWriter<'a> {
+    fn new(buffer: &'a mut [u8]) -> Self {
+        Self { buffer, len: 0 }
+    }
+}
+
+impl Write for BufWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buffer.len() {
+            return Err(core::fmt::Error);
+        }
+        self.buffer[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Formats into the buffer, returning the amount of bytes written
+fn write_to(
+    buffer: &mut [u8],
+    f: impl FnOnce(&mut BufWriter) -> core::fmt::Result,
+) -> Result<usize> {
+    let mut writer = BufWriter::new(buffer);
+    f(&mut writer).map_err(|_| SHTError::BufferTooSmallError)?;
+    Ok(writer.len)
+}
+
+/// JSON has no representation for NaN or infinities
+fn write_json_number(writer: &mut BufWriter, value: f32) -> core::fmt::Result {
+    if value.is_finite() {
+        write!(writer, "{value:.2}")
+    } else {
+        writer.write_str("null")
+    }
+}
+
+/// Most decimals [Reading::write_to] renders
+const MAX_FIXED_DECIMALS: usize = 4;
+
+/// Writes the value rounded to the given decimals using integer formatting only,
+/// which keeps float formatting out of the binary
+fn write_fixed<W: Write>(writer: &mut W, value: f32, decimals: usize) -> core::fmt::Result {
+    let decimals = decimals.min(MAX_FIXED_DECIMALS);
+    let scale = 10i32.pow(decimals as u32);
+    let scaled = libm::roundf(value * scale as f32) as i32;
+
+    if scaled < 0 {
+        writer.write_char('-')?;
+    }
+    let scaled = scaled.unsigned_abs();
+    let scale = scale as u32;
+    write!(writer, "{}", scaled / scale)?;
+    if decimals > 0 {
+        write!(writer, ".{:0width$}", scaled % scale, width = decimals)?;
+    }
+    Ok(())
+}
+
+impl Reading {
+    /// Writes the temperature with its unit, e.g. `23.4°C`. At most 4 decimals are
+    /// rendered and no float formatting is involved
+    pub fn write_temperature<W: Write>(
+        &self,
+        writer: &mut W,
+        decimals: usize,
+    ) -> core::fmt::Result {
+        write_fixed(writer, self.temperature, decimals)?;
+        write!(writer, "{}", self.unit)
+    }
+
+    /// Writes the humidity with its unit, e.g. `41.2%RH`, see [Reading::write_temperature]
+    pub fn write_humidity<W: Write>(&self, writer: &mut W, decimals: usize) -> core::fmt::Result {
+        write_fixed(writer, self.humidity, decimals)?;
+        writer.write_str("%RH")
+    }
+
+    /// Writes both values like [Display](core::fmt::Display) does but with the given
+    /// decimals, e.g. `23.4°C 41.2%RH`
+    pub fn write_to<W: Write>(&self, writer: &mut W, decimals: usize) -> core::fmt::Result {
+        self.write_temperature(writer, decimals)?;
+        writer.write_char(' ')?;
+        self.write_humidity(writer, decimals)
+    }
+
+    /// Renders the reading as a JSON object with two decimals, e.g.
+    /// `{"temperature":23.44,"humidity":41.25,"unit":"C"}`.
+    /// Returns the amount of bytes written
+    pub fn write_json(&self, buffer: &mut [u8]) -> Result<usize> {
+        let unit = match self.unit {
+            TemperatureUnit::Celsius => "C",
+            TemperatureUnit::Fahrenheit => "F",
+        };
+
+        write_to(buffer, |writer| {
+            writer.write_str("{\"temperature\":")?;
+            write_json_number(writer, self.temperature)?;
+            writer.write_str(",\"humidity\":")?;
+            write_json_number(writer, self.humidity)?;
+            write!(writer, ",\"unit\":\"{unit}\"}}")
+        })
+    }
+
+    /// Renders a `timestamp,temperature,humidity` CSV line, newline included, with the
+    /// given amount of decimals. Returns the amount of bytes written
+    pub fn write_csv(&self, timestamp: u32, decimals: usize, buffer: &mut [u8]) -> Result<usize> {
+        LogEntry {
+            reading: *self,
+            timestamp: Some(timestamp),
+        }
+        .write_csv(decimals, buffer)
+    }
+}
+
+impl LogEntry {
+    /// Same as [Reading::write_csv], the timestamp field is left empty if there's none
+    pub fn write_csv(&self, decimals: usize, buffer: &mut [u8]) -> Result<usize> {
+        write_to(buffer, |writer| {
+            if let Some(timestamp) = self.timestamp {
+                write!(writer, "{timestamp}")?;
+            }
+            writeln!(
+                writer,
+                ",{:.*},{:.*}",
+                decimals, self.reading.temperature, decimals, self.reading.humidity
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json() {
+        let reading = Reading {
+            temperature: 23.444,
+            humidity: 41.25,
+            unit: TemperatureUnit::Celsius,
+        };
+        let mut buffer = [0; 64];
+
+        let len = reading.write_json(&mut buffer).unwrap();
+        assert_eq!(
+            &buffer[..len],
+            br#"{"temperature":23.44,"humidity":41.25,"unit":"C"}"#
+        );
+
+        let reading = Reading {
+            temperature: f32::NAN,
+            ..reading
+        };
+        let len = reading.write_json(&mut buffer).unwrap();
+        assert_eq!(
+            &buffer[..len],
+            br#"{"temperature":null,"humidity":41.25,"unit":"C"}"#
+        );
+
+        assert_eq!(
+            reading.write_json(&mut [0; 16]),
+            Err(SHTError::BufferTooSmallError)
+        );
+    }
+
+    #[test]
+    fn csv() {
+        let reading = Reading {
+            temperature: 23.444,
+            humidity: 41.25,
+            unit: TemperatureUnit::Celsius,
+        };
+        let mut buffer = [0; 32];
+
+        let len = reading.write_csv(1500, 1, &mut buffer).unwrap();
+        assert_eq!(&buffer[..len], b"1500,23.4,41.2\n");
+
+        let entry = LogEntry {
+            reading,
+            timestamp: None,
+        };
+        let len = entry.write_csv(3, &mut buffer).unwrap();
+        assert_eq!(&buffer[..len], b",23.444,41.250\n");
+
+        assert_eq!(
+            reading.write_csv(1500, 2, &mut [0; 8]),
+            Err(SHTError::BufferTooSmallError)
+        );
+    }
+
+    #[test]
+    fn fixed_point() {
+        extern crate alloc;
+        use alloc::string::String;
+
+        let reading = Reading {
+            temperature: -0.25,
+            humidity: 41.256,
+            unit: TemperatureUnit::Celsius,
+        };
+
+        let mut text = String::new();
+        reading.write_to(&mut text, 1).unwrap();
+        assert_eq!(text, "-0.3°C 41.3%RH");
+
+        text.clear();
+        reading.write_humidity(&mut text, 0).unwrap();
+        reading.write_temperature(&mut text, 8).unwrap();
+        assert_eq!(text, "41%RH-0.2500°C");
+    }
+}
@@ -0,0 +1,116 @@
+//! Link quality counters kept by the driver across its lifetime
+use crate::{error::Result, SHTError, SHT31};
+
+/// Counts of the failures seen since the sensor was created or the counters were reset
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Health {
+    /// Failed I2C transactions, a sensor that has no data ready yet isn't counted
+    pub i2c_errors: u32,
+    /// Words whose CRC didn't match
+    pub crc_errors: u32,
+    /// Measurements or commands that didn't complete in time
+    pub timeouts: u32,
+}
+
+impl Health {
+    /// Total amount of failures
+    pub fn total(&self) -> u32 {
+        self.i2c_errors + self.crc_errors + self.timeouts
+    }
+
+    fn record(&mut self, error: &SHTError) {
+        let counter = match error {
+            SHTError::ReadI2CError | SHTError::WriteI2CError | SHTError::WriteReadI2CError => {
+                &mut self.i2c_errors
+            }
+            SHTError::InvalidHumidityChecksumError { .. }
+            | SHTError::InvalidTemperatureChecksumError { .. }
+            | SHTError::InvalidStatusChecksumError { .. }
+            | SHTError::InvalidSerialNumberChecksumError { .. } => &mut self.crc_errors,
+            SHTError::ReadingTimeoutError { .. }
+            | SHTError::FetchTimeoutError
+            | SHTError::CommandTimeoutError => &mut self.timeouts,
+            _ => return,
+        };
+        *counter = counter.saturating_add(1);
+    }
+}
+
+impl<Mode, I2C, C> SHT31<Mode, I2C, C> {
+    /// The failures counted so far
+    pub fn health(&self) -> Health {
+        self.health
+    }
+
+    /// Start counting failures from zero
+    pub fn reset_health(&mut self) {
+        self.health = Health::default();
+    }
+
+    /// Counts the error, if any, before handing the result back
+    pub(crate) fn track<T>(&mut self, result: Result<T>) -> Result<T> {
+        if let Err(error) = &result {
+            self.health.record(error);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use crate::{prelude::*, SHTError};
+    use alloc::vec;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    #[test]
+    fn counts_failures() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x24, 0x00]).with_error(ErrorKind::Other),
+            Transaction::write(0x44, vec![0x24, 0x00]),
+            Transaction::read(0x44, vec![98, 153, 0, 98, 32, 139]),
+            Transaction::write_read(0x44, vec![0xF3, 0x2D], vec![0x00, 0x02, 0xE3]),
+            Transaction::write_read(0x44, vec![0xF3, 0x2D], vec![0x00, 0x00, 0x00]),
+        ];
+        let mut sensor = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+
+        assert!(sensor.measure_and_wait(&mut NoopDelay).is_err());
+        assert!(sensor.measure_and_wait(&mut NoopDelay).is_err());
+        assert_eq!(
+            sensor.wait_for_command_processed(&mut NoopDelay, 1).err(),
+            Some(SHTError::CommandTimeoutError)
+        );
+        assert!(sensor.status().is_err());
+
+        let health = sensor.health();
+        assert_eq!(health.i2c_errors, 1);
+        assert_eq!(health.crc_errors, 2);
+        assert_eq!(health.timeouts, 1);
+        assert_eq!(health.total(), 4);
+
+        sensor.reset_health();
+        assert_eq!(sensor.health(), Health::default());
+
+        sensor.destroy().done();
+    }
+
+    #[test]
+    fn not_ready_is_not_counted() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x24, 0x00]),
+            Transaction::read(0x44, vec![0; 6])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+        ];
+        let mut sensor = SHT31::single_shot(Mock::new(&expectations), SingleShot::new())
+            .measure()
+            .unwrap();
+        assert_eq!(sensor.read_nb().err(), Some(nb::Error::WouldBlock));
+        assert_eq!(sensor.health().total(), 0);
+
+        sensor.destroy().done();
+    }
+}
@@ -0,0 +1,484 @@
+//! Helpers built on top of the onboard heater
+use crate::{
+    clock::{Clock, MaybeClock},
+    error::{Result, SHTError},
+    mode::Sht31Sensor,
+    Reading, SHT31,
+};
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+
+/// How long the heater runs during [SHT31::self_test]
+const SELF_TEST_HEAT_MS: u32 = 5000;
+/// Minimum temperature rise in Celsius a working sensor shows while heated
+const SELF_TEST_MIN_RISE: f32 = 0.5;
+/// Minimum relative humidity drop in %RH a working sensor shows while heated
+const SELF_TEST_MIN_DROP: f32 = 1f32;
+
+/// The verdict of a [SHT31::self_test]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SelfTestOutcome {
+    Passed,
+    /// The temperature didn't rise, the sensor or its heater may be dead
+    NoTemperatureRise,
+    /// The humidity didn't fall, the sensor may be covered or contaminated
+    NoHumidityDrop,
+}
+
+/// The readings taken during a [SHT31::self_test] along with its verdict
+#[derive(Copy, Clone, Debug)]
+pub struct SelfTestReport {
+    pub baseline: Reading,
+    pub heated: Reading,
+    pub outcome: SelfTestOutcome,
+}
+
+/// Limits how long the heater may stay on and how long it must cool down
+/// before being turned on again, every path that switches the heater is checked.
+/// Time comes from the driver's [Clock], see [SHT31::with_clock]
+#[derive(Copy, Clone, Debug)]
+pub struct HeaterGuard {
+    max_on_ms: u32,
+    min_off_ms: u32,
+    /// Whether the heater is on and when it last changed state
+    last_change: Option<(bool, u32)>,
+}
+
+impl HeaterGuard {
+    pub fn new(max_on_ms: u32, min_off_ms: u32) -> Self {
+        Self {
+            max_on_ms,
+            min_off_ms,
+            last_change: None,
+        }
+    }
+
+    /// Checks the heater may change into the given state at `now`
+    fn check(&self, heating: bool, now: u32) -> Result<()> {
+        match self.last_change {
+            Some((false, since)) if heating && now.wrapping_sub(since) < self.min_off_ms => {
+                Err(SHTError::HeaterCooldownError)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the heater may run for the given milliseconds in one go
+    fn check_duration(&self, ms: u32) -> Result<()> {
+        if ms > self.max_on_ms {
+            return Err(SHTError::HeaterOnTimeError);
+        }
+        Ok(())
+    }
+
+    /// Whether the heater has been on for longer than allowed at `now`
+    fn exceeded(&self, now: u32) -> bool {
+        matches!(self.last_change, Some((true, since)) if now.wrapping_sub(since) > self.max_on_ms)
+    }
+
+    fn record(&mut self, heating: bool, now: u32) {
+        let changed = self.last_change.is_none_or(|(on, _)| on != heating);
+        if changed {
+            self.last_change = Some((heating, now));
+        }
+    }
+}
+
+impl<Mode, I2C, C> SHT31<Mode, I2C, C>
+where
+    C: MaybeClock,
+{
+    /// Notes the heater's current state in the guard and the self-heating compensation,
+    /// also for resets that turn it off
+    pub(crate) fn record_heater(&mut self) {
+        let now = self.clock.try_now_ms();
+        // A guard is only ever set alongside a clock
+        if let (Some(guard), Some(now)) = (&mut self.heater_guard, now) {
+            guard.record(self.heater, now);
+        }
+        self.compensation.heater_changed(self.heater, now);
+    }
+}
+
+impl<Mode, I2C, C> SHT31<Mode, I2C, C>
+where
+    I2C: I2c,
+    C: Clock,
+{
+    /// Enforce a heater duty-cycle on every heater switch, see [SHT31::check_heater]
+    pub fn set_heater_guard(&mut self, guard: HeaterGuard) {
+        self.heater_guard = Some(guard);
+    }
+
+    /// Enforce a heater duty-cycle on every heater switch, see [SHT31::check_heater]
+    pub fn with_heater_guard(mut self, guard: HeaterGuard) -> Self {
+        self.set_heater_guard(guard);
+        self
+    }
+
+    /// Turns the heater off if it has exceeded the heater guard's maximum on time,
+    /// returning [SHTError::HeaterOnTimeError] when it had to be forced off
+    pub fn check_heater(&mut self) -> Result<()> {
+        let now = self.clock.now_ms();
+        match self.heater_guard {
+            Some(guard) if guard.exceeded(now) => {
+                self.set_heating(false)?;
+                Err(SHTError::HeaterOnTimeError)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<Mode, I2C, C> SHT31<Mode, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Switches the heater, every path goes through here so the guard can't be bypassed.
+    /// Fails with [SHTError::HeaterCooldownError] if the guard's minimum off time hasn't passed
+    pub(crate) fn switch_heater_guarded(&mut self, heating: bool) -> Result<()> {
+        let now = self.clock.try_now_ms();
+        if let (Some(guard), Some(now)) = (&self.heater_guard, now) {
+            guard.check(heating, now)?;
+        }
+
+        self.heater = heating;
+        self.switch_heater()?;
+        self.record_heater();
+        Ok(())
+    }
+
+    /// Fails with [SHTError::HeaterOnTimeError] if heating for the given milliseconds
+    /// would exceed the guard's maximum on time
+    fn check_heat_duration(&self, ms: u32) -> Result<()> {
+        match &self.heater_guard {
+            Some(guard) => guard.check_duration(ms),
+            None => Ok(()),
+        }
+    }
+
+    /// Reads back the status register and checks the heater matches the requested state
+    fn verify_heater(&mut self) -> Result<()> {
+        if self.status()?.heater_on != self.heater {
+            return Err(SHTError::HeaterStateError);
+        }
+        Ok(())
+    }
+
+    /// Set the heater's heating state and confirm it through the status register,
+    /// fails with [SHTError::HeaterStateError] if the heater didn't follow
+    pub fn set_heating_verified(&mut self, heating: bool) -> Result<()> {
+        self.set_heating(heating)?;
+        self.verify_heater()
+    }
+
+    /// Turns the heater on for the given milliseconds and then back off, verifying
+    /// each transition through the status register. Useful for decondensation
+    /// or plausibility checks
+    pub fn heat_for<D: DelayNs>(&mut self, ms: u32, delay: &mut D) -> Result<()> {
+        self.check_heat_duration(ms)?;
+
+        if let Err(err) = self.set_heating_verified(true) {
+            // Don't leave the heater running
+            if self.heater {
+                self.set_heating(false)?;
+            }
+            return Err(err);
+        }
+
+        delay.delay_ms(ms);
+
+        self.set_heating_verified(false)
+    }
+}
+
+impl<Mode, I2C, C> SHT31<Mode, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+    Self: Sht31Sensor,
+{
+    /// Reverses humidity creep after exposure to condensation by heating for `heat_ms`,
+    /// waiting `settle_ms` for the sensor to cool down and discarding the first reading
+    /// since it's still skewed by the heater. Meant to be triggered on a schedule
+    pub fn recondition<D: DelayNs>(
+        &mut self,
+        heat_ms: u32,
+        settle_ms: u32,
+        delay: &mut D,
+    ) -> Result<()> {
+        self.heat_for(heat_ms, delay)?;
+        delay.delay_ms(settle_ms);
+        self.sample(delay).map(|_| ())
+    }
+
+    /// Heats the sensor and checks the temperature rises and the humidity falls,
+    /// detecting dead or covered sensors. The heater is always turned back off
+    pub fn self_test<D: DelayNs>(&mut self, delay: &mut D) -> Result<SelfTestReport> {
+        self.check_heat_duration(SELF_TEST_HEAT_MS)?;
+        let baseline = self.sample(delay)?;
+
+        let heated = self.set_heating_verified(true).and_then(|_| {
+            delay.delay_ms(SELF_TEST_HEAT_MS);
+            self.sample(delay)
+        });
+        self.set_heating(false)?;
+        let heated = heated?;
+
+        let outcome = if heated.celsius() - baseline.celsius() < SELF_TEST_MIN_RISE {
+            SelfTestOutcome::NoTemperatureRise
+        } else if baseline.humidity - heated.humidity < SELF_TEST_MIN_DROP {
+            SelfTestOutcome::NoHumidityDrop
+        } else {
+            SelfTestOutcome::Passed
+        };
+
+        Ok(SelfTestReport {
+            baseline,
+            heated,
+            outcome,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use super::{HeaterGuard, SelfTestOutcome};
+    use crate::prelude::*;
+    use crate::SHTError;
+    use alloc::vec;
+    use core::cell::Cell;
+    use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    use rstest::rstest;
+
+    const ADDR: u8 = 0x44;
+
+    fn heater(on: bool) -> Transaction {
+        Transaction::write(ADDR, vec![0x30, if on { 0x6D } else { 0x66 }])
+    }
+
+    fn status(heater_on: bool) -> Transaction {
+        let response = if heater_on {
+            vec![0x20, 0x00, 0x5D]
+        } else {
+            vec![0x00, 0x00, 0x81]
+        };
+        Transaction::write_read(ADDR, vec![0xF3, 0x2D], response)
+    }
+
+    #[test]
+    fn set_heating_verified() {
+        let expectations = [heater(true), status(true), heater(false), status(true)];
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+        sht31.set_heating_verified(true).unwrap();
+        assert_eq!(
+            sht31.set_heating_verified(false).err().unwrap(),
+            SHTError::HeaterStateError
+        );
+
+        sht31.destroy().done();
+    }
+
+    #[test]
+    fn heat_for() {
+        let expectations = [heater(true), status(true), heater(false), status(false)];
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(500)]);
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+        sht31.heat_for(500, &mut delay).unwrap();
+
+        sht31.destroy().done();
+        delay.done();
+    }
+
+    #[test]
+    fn heat_for_unconfirmed() {
+        let expectations = [heater(true), status(false), heater(false)];
+        let mut delay = CheckedDelay::new(&[]);
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+        assert_eq!(
+            sht31.heat_for(500, &mut delay).err().unwrap(),
+            SHTError::HeaterStateError
+        );
+
+        sht31.destroy().done();
+        delay.done();
+    }
+
+    #[test]
+    fn recondition_single_shot() {
+        let mut expectations = vec![heater(true), status(true), heater(false), status(false)];
+        expectations.extend(crate::test_utils::single_shot_expectations(
+            ADDR,
+            Accuracy::High,
+            false,
+            [98, 153, 188, 98, 32, 139],
+        ));
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_ms(10_000),
+            DelayTransaction::delay_ms(60_000),
+            DelayTransaction::delay_us(15_500),
+        ]);
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+        sht31.recondition(10_000, 60_000, &mut delay).unwrap();
+
+        sht31.destroy().done();
+        delay.done();
+    }
+
+    #[test]
+    fn recondition() {
+        let expectations = [
+            heater(true),
+            status(true),
+            heater(false),
+            status(false),
+            Transaction::transaction_start(ADDR),
+            Transaction::write(ADDR, vec![0x2C, 0x06]),
+            Transaction::read(ADDR, vec![98, 153, 188, 98, 32, 139]),
+            Transaction::transaction_end(ADDR),
+        ];
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_ms(10_000),
+            DelayTransaction::delay_ms(60_000),
+        ]);
+
+        let mut sht31 = SHT31::clock_stretch(Mock::new(&expectations), ClockStretch::new());
+        sht31.recondition(10_000, 60_000, &mut delay).unwrap();
+
+        sht31.destroy().done();
+        delay.done();
+    }
+
+    fn self_test_expectations(heated: [u8; 6]) -> [Transaction; 11] {
+        [
+            Transaction::transaction_start(ADDR),
+            Transaction::write(ADDR, vec![0x2C, 0x06]),
+            Transaction::read(ADDR, vec![98, 153, 188, 98, 32, 139]),
+            Transaction::transaction_end(ADDR),
+            heater(true),
+            status(true),
+            Transaction::transaction_start(ADDR),
+            Transaction::write(ADDR, vec![0x2C, 0x06]),
+            Transaction::read(ADDR, heated.to_vec()),
+            Transaction::transaction_end(ADDR),
+            heater(false),
+        ]
+    }
+
+    #[rstest]
+    #[case([100, 0, 87, 90, 0, 136], SelfTestOutcome::Passed)]
+    #[case([98, 153, 188, 90, 0, 136], SelfTestOutcome::NoTemperatureRise)]
+    #[case([100, 0, 87, 98, 32, 139], SelfTestOutcome::NoHumidityDrop)]
+    fn self_test(#[case] heated: [u8; 6], #[case] outcome: SelfTestOutcome) {
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(5000)]);
+
+        let mut sht31 = SHT31::clock_stretch(
+            Mock::new(&self_test_expectations(heated)),
+            ClockStretch::new(),
+        );
+        let report = sht31.self_test(&mut delay).unwrap();
+        assert_eq!(report.outcome, outcome);
+
+        sht31.destroy().done();
+        delay.done();
+    }
+
+    #[test]
+    fn self_test_single_shot() {
+        let frame =
+            |bytes| crate::test_utils::single_shot_expectations(ADDR, Accuracy::High, false, bytes);
+        let mut expectations = vec![];
+        expectations.extend(frame([98, 153, 188, 98, 32, 139]));
+        expectations.extend([heater(true), status(true)]);
+        expectations.extend(frame([100, 0, 87, 90, 0, 136]));
+        expectations.push(heater(false));
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_us(15_500),
+            DelayTransaction::delay_ms(5000),
+            DelayTransaction::delay_us(15_500),
+        ]);
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+        let report = sht31.self_test(&mut delay).unwrap();
+        assert_eq!(report.outcome, SelfTestOutcome::Passed);
+
+        sht31.destroy().done();
+        delay.done();
+    }
+
+    #[test]
+    fn heater_guard() {
+        let now = Cell::new(0);
+        let expectations = [heater(true), heater(false), heater(true)];
+        let mut delay = CheckedDelay::new(&[]);
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new())
+            .with_clock(|| now.get())
+            .with_heater_guard(HeaterGuard::new(1000, 5000));
+
+        sht31.set_heating(true).unwrap();
+        now.set(1000);
+        sht31.check_heater().unwrap();
+        now.set(1001);
+        assert_eq!(
+            sht31.check_heater().err().unwrap(),
+            SHTError::HeaterOnTimeError
+        );
+        now.set(6000);
+        assert_eq!(
+            sht31.set_heating(true).err().unwrap(),
+            SHTError::HeaterCooldownError
+        );
+        now.set(6001);
+        sht31.set_heating(true).unwrap();
+
+        assert_eq!(
+            sht31.heat_for(1001, &mut delay).err().unwrap(),
+            SHTError::HeaterOnTimeError
+        );
+
+        sht31.destroy().done();
+        delay.done();
+    }
+
+    #[test]
+    fn heat_for_cooldown() {
+        let now = Cell::new(0);
+        let expectations = [
+            heater(true),
+            status(true),
+            heater(false),
+            status(false),
+            heater(true),
+            status(true),
+            heater(false),
+            status(false),
+        ];
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_ms(500),
+            DelayTransaction::delay_ms(500),
+        ]);
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new())
+            .with_clock(|| now.get())
+            .with_heater_guard(HeaterGuard::new(1000, 5000));
+
+        sht31.heat_for(500, &mut delay).unwrap();
+        now.set(4999);
+        assert_eq!(
+            sht31.heat_for(500, &mut delay).err().unwrap(),
+            SHTError::HeaterCooldownError
+        );
+        now.set(5000);
+        sht31.heat_for(500, &mut delay).unwrap();
+
+        sht31.destroy().done();
+        delay.done();
+    }
+}
@@ -1,30 +1,108 @@
 #![no_std]
-
+// Failed transitions hand the sensor back, which makes for large errors by design
+#![allow(clippy::result_large_err)]
+
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod cache;
+pub mod checksum;
+pub mod clock;
+pub mod command;
+pub mod compensation;
+pub mod derived;
+#[cfg(feature = "fugit")]
+pub mod duration;
+#[cfg(feature = "embassy")]
+pub mod embassy;
 pub mod error;
+#[cfg(feature = "fake")]
+pub mod fake;
+pub mod filter;
+pub mod format;
+pub mod health;
+pub mod heater;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod linux;
+pub mod ll;
+pub mod logger;
 pub mod mode;
+pub mod pair;
+pub mod power;
+#[cfg(any(feature = "uom", feature = "measurements"))]
+pub mod quantity;
+pub mod recovery;
+pub mod scan;
+#[cfg(feature = "embedded-sensors")]
+pub mod sensors;
+#[cfg(feature = "embedded-hal-bus")]
+pub mod shared;
+pub mod stats;
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(feature = "critical-section")]
+pub mod sync;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod watch;
+pub mod watchdog;
+pub mod wire;
+
+use crate::cache::ReadingCache;
+use crate::checksum::{Checksum, ChecksumPolicy, SoftwareCrc};
+use crate::clock::{Clock, MaybeClock, NoClock};
+use crate::command::Command;
+use crate::compensation::{Calibration, Compensation, SelfHeating};
+use crate::health::Health;
+use crate::heater::HeaterGuard;
+use crate::mode::{
+    ClockStretch, CountDown, Idle, Periodic, SimpleSingleShot, SingleShot, TimedSingleShot, MPS,
+};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::OutputPin,
+    i2c::{I2c, Operation},
+};
 
-use crate::mode::{Periodic, SimpleSingleShot, SingleShot};
-use crc::{Algorithm, Crc};
-use embedded_hal::{delay::DelayNs, i2c::I2c};
-
-pub use crate::error::{Result, SHTError};
+pub use crate::error::{Result, SHTError, TransitionError, TransitionResult};
 pub mod prelude {
     pub use super::{
-        mode::{Periodic, Sht31Measure, Sht31Reader, SimpleSingleShot, SingleShot, MPS},
+        checksum::{Checksum, ChecksumPolicy, SoftwareCrc},
+        clock::{Clock, MaybeClock, NoClock, Timestamped, TimestampedReading},
+        command::Command,
+        compensation::{Calibration, Linear, SelfHeating},
+        filter::{Ema, Filtered, ThermalLag, Window},
+        health::Health,
+        heater::{HeaterGuard, SelfTestOutcome, SelfTestReport},
+        logger::{LogEntry, ReadingLog},
+        mode::{
+            Backoff, ClockStretch, CountDown, DynSht31Reader, FrequencyRounding, Idle, Periodic,
+            Sht31Measure, Sht31Reader, Sht31Sensor, SimpleSingleShot, SingleShot, SplitFetch,
+            TimedSingleShot, MPS,
+        },
+        pair::{Sht31Pair, Tolerance},
+        recovery::{ReadOutcome, Rearm, Recovering},
+        scan::{scan, ScannedDevice},
+        stats::{Averaged, Stats, Summary},
+        watch::{Threshold, Watcher},
+        watchdog::{StuckDetector, Watchdog},
         Accuracy, DeviceAddr, Reading, Status, TemperatureUnit, SHT31,
     };
+
+    #[cfg(feature = "fake")]
+    pub use super::fake::{FakeSht31, FakeSource};
+    #[cfg(feature = "critical-section")]
+    pub use super::sync::SharedSensor;
 }
 
-const CRC_ALGORITHM: Algorithm<u8> = Algorithm {
-    width: 8,
-    poly: 0x31,
-    init: 0xFF,
-    refin: false,
-    refout: false,
-    xorout: 0x00,
-    check: 0x00,
-    residue: 0x00,
-};
+/// Minimum time nRESET must be held low to trigger a reset
+const RESET_PULSE_US: u32 = 1;
+/// Time the sensor needs after a reset before it accepts commands
+const RESET_DELAY_US: u32 = 1500;
+/// Address every device listens to for general calls
+const GENERAL_CALL_ADDRESS: u8 = 0x00;
+/// Time the sensor needs after power-up before it accepts commands
+const POWER_UP_DELAY_MS: u32 = 1;
 
 // 2**16 - 1
 const CONVERSION_DENOM: f32 = 65535f32;
@@ -33,36 +111,142 @@ const CONVERSION_DENOM: f32 = 65535f32;
 const CELSIUS_PAIR: (f32, f32) = (45f32, 175f32);
 const FAHRENHEIT_PAIR: (f32, f32) = (49f32, 315f32);
 
+/// Temperatures in Celsius a working sensor can report
+const PLAUSIBLE_CELSIUS: (f32, f32) = (-45f32, 130f32);
+
 /// The temperature and humidity sensor
 #[derive(Copy, Clone, Debug)]
-pub struct SHT31<Mode, I2C> {
+pub struct SHT31<Mode, I2C, C = NoClock> {
     mode: Mode,
     i2c: I2C,
     address: u8,
     accuracy: Accuracy,
     unit: TemperatureUnit,
     heater: bool,
+    heater_guard: Option<HeaterGuard>,
+    compensation: Compensation,
+    plausibility_check: bool,
+    health: Health,
+    cache: ReadingCache,
+    checksum: ll::ChecksumFn,
+    checksum_policy: ChecksumPolicy,
+    checksum_failed: bool,
+    clock: C,
+}
+
+/// Acknowledgment required by [SHT31::general_call_reset], that the reset
+/// isn't limited to the sensor but reaches every device on the bus
+#[derive(Copy, Clone, Debug)]
+pub struct GeneralCall {
+    _private: (),
+}
+
+impl GeneralCall {
+    pub fn resets_every_device_on_the_bus() -> Self {
+        Self { _private: () }
+    }
 }
 
 /// Represents the reading gotten from the sensor
-#[derive(Default, Clone, Copy, Debug)]
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Reading {
     pub temperature: f32,
     pub humidity: f32,
+    /// The unit the temperature is expressed in
+    pub unit: TemperatureUnit,
+}
+
+impl Reading {
+    /// Whether both values are within the given differences, the temperature difference is
+    /// in this reading's unit and the other reading is converted to it
+    pub fn approx_eq(&self, other: &Reading, temperature_eps: f32, humidity_eps: f32) -> bool {
+        let other_temperature = self.in_unit(other.celsius());
+
+        (self.temperature - other_temperature).abs() <= temperature_eps
+            && (self.humidity - other.humidity).abs() <= humidity_eps
+    }
+}
+
+/// Readings in different units are converted to the left hand side's unit
+impl core::ops::Add for Reading {
+    type Output = Reading;
+
+    fn add(self, rhs: Reading) -> Reading {
+        Reading {
+            temperature: self.temperature + self.in_unit(rhs.celsius()),
+            humidity: self.humidity + rhs.humidity,
+            unit: self.unit,
+        }
+    }
+}
+
+/// Readings in different units are converted to the left hand side's unit
+impl core::ops::Sub for Reading {
+    type Output = Reading;
+
+    fn sub(self, rhs: Reading) -> Reading {
+        Reading {
+            temperature: self.temperature - self.in_unit(rhs.celsius()),
+            humidity: self.humidity - rhs.humidity,
+            unit: self.unit,
+        }
+    }
+}
+
+impl core::ops::Mul<f32> for Reading {
+    type Output = Reading;
+
+    fn mul(self, rhs: f32) -> Reading {
+        Reading {
+            temperature: self.temperature * rhs,
+            humidity: self.humidity * rhs,
+            unit: self.unit,
+        }
+    }
+}
+
+impl core::ops::Div<f32> for Reading {
+    type Output = Reading;
+
+    fn div(self, rhs: f32) -> Reading {
+        Reading {
+            temperature: self.temperature / rhs,
+            humidity: self.humidity / rhs,
+            unit: self.unit,
+        }
+    }
 }
 
-/// The two supported I2C addresses
+/// The two supported I2C addresses, or a custom one for when the sensor
+/// is remapped behind an address translator or mux
 #[allow(dead_code)]
 #[derive(Default, Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DeviceAddr {
     #[default]
-    AD0 = 0x44,
-    AD1 = 0x45,
+    AD0,
+    AD1,
+    Custom(u8),
+}
+
+impl From<DeviceAddr> for u8 {
+    fn from(address: DeviceAddr) -> Self {
+        match address {
+            DeviceAddr::AD0 => 0x44,
+            DeviceAddr::AD1 => 0x45,
+            DeviceAddr::Custom(address) => address,
+        }
+    }
 }
 
 /// Influences what the reading temperature numbers are
 #[allow(dead_code)]
 #[derive(Default, Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TemperatureUnit {
     Celsius,
     #[default]
@@ -73,6 +257,8 @@ pub enum TemperatureUnit {
 /// the longer it'll take and the more accurate it will be
 #[allow(dead_code)]
 #[derive(Default, Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Accuracy {
     #[default]
     High,
@@ -80,7 +266,55 @@ pub enum Accuracy {
     Low,
 }
 
+impl Accuracy {
+    /// The datasheet's maximum single shot conversion time in microseconds
+    pub fn max_duration_us(&self) -> u32 {
+        match self {
+            Accuracy::High => 15_500,
+            Accuracy::Medium => 6_500,
+            Accuracy::Low => 4_500,
+        }
+    }
+
+    /// The datasheet's maximum single shot conversion time in milliseconds
+    pub fn max_duration_ms(&self) -> f32 {
+        self.max_duration_us() as f32 / 1000f32
+    }
+
+    /// The datasheet's typical temperature repeatability in °C, the spread of
+    /// consecutive measurements under constant conditions
+    pub fn temperature_repeatability(&self) -> f32 {
+        match self {
+            Accuracy::High => 0.04,
+            Accuracy::Medium => 0.08,
+            Accuracy::Low => 0.15,
+        }
+    }
+
+    /// The datasheet's typical relative humidity repeatability in %RH
+    pub fn humidity_repeatability(&self) -> f32 {
+        match self {
+            Accuracy::High => 0.08,
+            Accuracy::Medium => 0.15,
+            Accuracy::Low => 0.21,
+        }
+    }
+
+    /// The fastest accuracy whose repeatability is within both bounds, [None] if not
+    /// even [Accuracy::High] is
+    pub fn fastest_within(temperature: f32, humidity: f32) -> Option<Accuracy> {
+        [Accuracy::Low, Accuracy::Medium, Accuracy::High]
+            .into_iter()
+            .find(|accuracy| {
+                accuracy.temperature_repeatability() <= temperature
+                    && accuracy.humidity_repeatability() <= humidity
+            })
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Status {
     /// Last checksum transfer failed
     pub checksum_failed: bool,
@@ -96,14 +330,20 @@ pub struct Status {
     pub heater_on: bool,
     /// At least one pending alert
     pub pending_alert: bool,
+    /// The status register as read, including reserved bits
+    pub raw: u16,
 }
 
 fn bit_flag(n: u16, pos: u8) -> bool {
     n & (1 << pos) != 0
 }
 
+/// Bits of the status word decoded into [Status] flags
+const STATUS_FLAGS_MASK: u16 = 1 | 1 << 1 | 1 << 4 | 1 << 10 | 1 << 11 | 1 << 13 | 1 << 15;
+
 impl Status {
-    fn from_bytes(bytes: u16) -> Self {
+    /// Decodes a status word as read from the sensor
+    pub fn from_bytes(bytes: u16) -> Self {
         Self {
             checksum_failed: bit_flag(bytes, 0),
             last_command_processed: !bit_flag(bytes, 1),
@@ -112,46 +352,141 @@ impl Status {
             rh_alert: bit_flag(bytes, 11),
             heater_on: bit_flag(bytes, 13),
             pending_alert: bit_flag(bytes, 15),
+            raw: bytes,
         }
     }
-}
 
-fn calculate_checksum(crc: &Crc<u8>, msb: u8, lsb: u8) -> u8 {
-    let mut digest = crc.digest();
-    digest.update(&[msb, lsb]);
-    digest.finalize()
+    /// Encodes the flags back into a status word, reserved bits are kept from [Status::raw]
+    pub fn to_bytes(&self) -> u16 {
+        let flags = [
+            (self.checksum_failed, 0),
+            (!self.last_command_processed, 1),
+            (self.system_reset, 4),
+            (self.t_alert, 10),
+            (self.rh_alert, 11),
+            (self.heater_on, 13),
+            (self.pending_alert, 15),
+        ];
+
+        flags
+            .into_iter()
+            .filter(|(set, _)| *set)
+            .fold(self.raw & !STATUS_FLAGS_MASK, |bytes, (_, pos)| {
+                bytes | 1 << pos
+            })
+    }
 }
 
-fn verify_reading(buffer: [u8; 6]) -> Result<()> {
-    let crc = Crc::<u8>::new(&CRC_ALGORITHM);
+impl core::fmt::Display for TemperatureUnit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TemperatureUnit::Celsius => f.write_str("°C"),
+            TemperatureUnit::Fahrenheit => f.write_str("°F"),
+        }
+    }
+}
 
-    let temp_result = calculate_checksum(&crc, buffer[0], buffer[1]);
-    if temp_result != buffer[2] {
-        return Err(SHTError::InvalidTemperatureChecksumError {
-            bytes_start: buffer[0],
-            bytes_end: buffer[1],
-            expected_checksum: buffer[2],
-            calculated_checksum: temp_result,
-        });
+impl core::fmt::Display for Reading {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:.1}{} {:.1}%RH",
+            self.temperature, self.unit, self.humidity
+        )
     }
+}
 
-    let humidity_result = calculate_checksum(&crc, buffer[3], buffer[4]);
-    if humidity_result != buffer[5] {
-        return Err(SHTError::InvalidHumidityChecksumError {
-            bytes_start: buffer[3],
-            bytes_end: buffer[4],
-            expected_checksum: buffer[5],
-            calculated_checksum: humidity_result,
-        });
+impl core::fmt::Display for Status {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let alerts = match (self.t_alert, self.rh_alert) {
+            (true, true) => "T,RH",
+            (true, false) => "T",
+            (false, true) => "RH",
+            (false, false) => "none",
+        };
+        let heater = if self.heater_on { "on" } else { "off" };
+        write!(f, "alerts: {alerts}; heater: {heater}")
     }
+}
 
+/// Holds nRESET low for the required pulse and waits for the sensor to come back up
+fn pulse_reset<P: OutputPin, D: DelayNs>(reset: &mut P, delay: &mut D) -> Result<()> {
+    reset.set_low().map_err(|_| SHTError::PinError)?;
+    delay.delay_us(RESET_PULSE_US);
+    reset.set_high().map_err(|_| SHTError::PinError)?;
+    delay.delay_us(RESET_DELAY_US);
     Ok(())
 }
 
-impl<Mode, I2C> SHT31<Mode, I2C> {
+/// Rejects physically impossible readings that still passed the checksum
+fn check_plausibility(reading: &Reading, raw_words: [f32; 2]) -> Result<()> {
+    let celsius = reading.celsius();
+    let (min, max) = PLAUSIBLE_CELSIUS;
+
+    let rails = raw_words
+        .iter()
+        .any(|&word| word == 0f32 || word == CONVERSION_DENOM);
+    if celsius <= min || celsius >= max || rails || !(0f32..=100f32).contains(&reading.humidity) {
+        return Err(SHTError::ImplausibleReadingError);
+    }
+    Ok(())
+}
+
+impl<Mode, I2C, C> SHT31<Mode, I2C, C>
+where
+    C: MaybeClock,
+{
     /// Verifies the two bytes against the returned checksum
-    fn verify_data(buffer: [u8; 6]) -> Result<()> {
-        verify_reading(buffer)
+    fn verify_data(&self, buffer: [u8; 6]) -> Result<()> {
+        ll::verify_measurement(self.checksum, buffer).map(|_| ())
+    }
+
+    fn process_data(&mut self, buffer: [u8; 6]) -> Result<Reading> {
+        self.process_data_at(buffer, None)
+    }
+
+    /// Same as [SHT31::process_data] for readings measured periodically at the given MPS
+    fn process_data_at(&mut self, buffer: [u8; 6], mps: Option<MPS>) -> Result<Reading> {
+        self.checksum_failed = false;
+        match self.checksum_policy {
+            ChecksumPolicy::Strict => {
+                let verified = self.verify_data(buffer);
+                self.track(verified)?;
+            }
+            ChecksumPolicy::Lenient => {
+                let verified = self.verify_data(buffer);
+                self.checksum_failed = self.track(verified).is_err();
+            }
+            ChecksumPolicy::Skip => {}
+        }
+
+        let raw_temp = u16::from_be_bytes([buffer[0], buffer[1]]) as f32;
+
+        let (sub, mul) = match self.unit {
+            TemperatureUnit::Celsius => CELSIUS_PAIR,
+            TemperatureUnit::Fahrenheit => FAHRENHEIT_PAIR,
+        };
+
+        let pre_sub = mul * (raw_temp / CONVERSION_DENOM);
+
+        let temperature = pre_sub - sub;
+
+        let raw_humidity = u16::from_be_bytes([buffer[3], buffer[4]]) as f32;
+        let humidity = 100f32 * raw_humidity / CONVERSION_DENOM;
+
+        let reading = Reading {
+            temperature,
+            humidity,
+            unit: self.unit,
+        };
+        let now = self.clock.try_now_ms();
+        let reading = self.compensation.apply(reading, self.heater, mps, now);
+
+        if self.plausibility_check {
+            check_plausibility(&reading, [raw_temp, raw_humidity])?;
+        }
+        self.cache.store(reading, now);
+        Ok(reading)
     }
 }
 
@@ -166,57 +501,115 @@ where
         Self::simple_single_shot(i2c, SimpleSingleShot::new(delay))
     }
 
-    pub fn simple_single_shot(i2c: I2C, mode: SimpleSingleShot<D>) -> Self {
-        Self {
-            mode,
-            i2c,
-            address: DeviceAddr::default() as u8,
-            unit: TemperatureUnit::default(),
-            accuracy: Accuracy::default(),
-            heater: false,
+    /// Create a new sensor on whichever of the two supported addresses responds
+    /// to a status read, [DeviceAddr::AD0] is probed first. The sensor is handed back
+    /// on failure so the bus isn't lost
+    pub fn autodetect(i2c: I2C, delay: D) -> TransitionResult<Self, Self> {
+        let mut sensor = Self::new(i2c, delay);
+
+        for address in [DeviceAddr::AD0, DeviceAddr::AD1] {
+            sensor.set_address(address);
+            if sensor.is_connected() {
+                return Ok(sensor);
+            }
         }
+
+        Err(TransitionError {
+            sensor,
+            error: SHTError::DeviceNotFoundError,
+        })
     }
 }
 
-impl<I2C> SHT31<Periodic, I2C>
+impl<I2C, D, Y> SHT31<SimpleSingleShot<D, Y>, I2C>
 where
     I2C: I2c,
+    D: DelayNs,
 {
+    pub fn simple_single_shot(i2c: I2C, mode: SimpleSingleShot<D, Y>) -> Self {
+        Self::with_defaults(mode, i2c)
+    }
+}
+
+impl<I2C> SHT31<Periodic, I2C> {
     pub fn periodic(i2c: I2C, mode: Periodic) -> SHT31<Periodic, I2C> {
-        Self {
-            mode,
-            i2c,
-            address: DeviceAddr::default() as u8,
-            unit: TemperatureUnit::default(),
-            accuracy: Accuracy::default(),
-            heater: false,
-        }
+        Self::with_defaults(mode, i2c)
     }
 }
 
-impl<I2C> SHT31<SingleShot, I2C>
-where
-    I2C: I2c,
-{
+impl<I2C> SHT31<SingleShot, I2C> {
     pub fn single_shot(i2c: I2C, mode: SingleShot) -> SHT31<SingleShot, I2C> {
+        Self::with_defaults(mode, i2c)
+    }
+}
+
+impl<I2C> SHT31<ClockStretch, I2C> {
+    pub fn clock_stretch(i2c: I2C, mode: ClockStretch) -> SHT31<ClockStretch, I2C> {
+        Self::with_defaults(mode, i2c)
+    }
+}
+
+impl<I2C, T: CountDown> SHT31<TimedSingleShot<T>, I2C> {
+    pub fn timed_single_shot(i2c: I2C, mode: TimedSingleShot<T>) -> SHT31<TimedSingleShot<T>, I2C> {
+        Self::with_defaults(mode, i2c)
+    }
+}
+
+impl<Mode, I2C> SHT31<Mode, I2C> {
+    /// A sensor in the given mode with the default configuration, every constructor
+    /// starts from here
+    fn with_defaults(mode: Mode, i2c: I2C) -> Self {
         Self {
             mode,
             i2c,
-            address: DeviceAddr::default() as u8,
+            address: DeviceAddr::default().into(),
             unit: TemperatureUnit::default(),
             accuracy: Accuracy::default(),
             heater: false,
+            heater_guard: None,
+            compensation: Compensation::default(),
+            plausibility_check: false,
+            health: Health::default(),
+            cache: ReadingCache::default(),
+            checksum: SoftwareCrc::checksum,
+            checksum_policy: ChecksumPolicy::Strict,
+            checksum_failed: false,
+            clock: NoClock,
         }
     }
 }
 
 #[allow(dead_code)]
-impl<Mode, I2C> SHT31<Mode, I2C>
-where
-    I2C: I2c,
-{
-    /// Changes the SHT31 mode
-    pub fn with_mode<NewMode>(self, mode: NewMode) -> SHT31<NewMode, I2C> {
+impl<Mode, I2C, C> SHT31<Mode, I2C, C> {
+    /// Gives the driver a clock for the [HeaterGuard], the heater decay of [SelfHeating]
+    /// and the reading cache
+    pub fn with_clock<K: Clock>(self, clock: K) -> SHT31<Mode, I2C, K> {
+        SHT31 {
+            mode: self.mode,
+            i2c: self.i2c,
+            address: self.address,
+            accuracy: self.accuracy,
+            unit: self.unit,
+            heater: self.heater,
+            heater_guard: self.heater_guard,
+            compensation: self.compensation,
+            plausibility_check: self.plausibility_check,
+            health: self.health,
+            cache: self.cache,
+            checksum: self.checksum,
+            checksum_policy: self.checksum_policy,
+            checksum_failed: self.checksum_failed,
+            clock,
+        }
+    }
+
+    /// Changes the SHT31 mode without telling the sensor, a sensor measuring periodically
+    /// keeps doing so and ignores the new mode's commands
+    #[deprecated(
+        since = "0.4.0",
+        note = "use the checked transitions, `into_single_shot`, `into_periodic` and `stop`, which break periodic measurements first"
+    )]
+    pub fn with_mode<NewMode>(self, mode: NewMode) -> SHT31<NewMode, I2C, C> {
         SHT31 {
             mode,
             i2c: self.i2c,
@@ -224,9 +617,92 @@ where
             accuracy: self.accuracy,
             unit: self.unit,
             heater: false,
+            heater_guard: self.heater_guard,
+            compensation: self.compensation,
+            plausibility_check: self.plausibility_check,
+            health: self.health,
+            cache: self.cache,
+            checksum: self.checksum,
+            checksum_policy: self.checksum_policy,
+            checksum_failed: self.checksum_failed,
+            clock: self.clock,
+        }
+    }
+
+    /// Moves into the new mode if the command succeeded, otherwise hands back the sensor
+    fn transition<NewMode>(
+        self,
+        result: Result<()>,
+        mode: NewMode,
+    ) -> TransitionResult<SHT31<NewMode, I2C, C>, Self> {
+        match result {
+            Ok(()) => Ok(self.into_mode(mode)),
+            Err(error) => Err(TransitionError {
+                sensor: self,
+                error,
+            }),
+        }
+    }
+
+    /// Moves into the new mode while keeping the rest of the sensor's state
+    fn into_mode<NewMode>(self, mode: NewMode) -> SHT31<NewMode, I2C, C> {
+        SHT31 {
+            mode,
+            i2c: self.i2c,
+            address: self.address,
+            accuracy: self.accuracy,
+            unit: self.unit,
+            heater: self.heater,
+            heater_guard: self.heater_guard,
+            compensation: self.compensation,
+            plausibility_check: self.plausibility_check,
+            health: self.health,
+            cache: self.cache,
+            checksum: self.checksum,
+            checksum_policy: self.checksum_policy,
+            checksum_failed: self.checksum_failed,
+            clock: self.clock,
         }
     }
 
+    /// Swaps everything but the bus and the clock with another device's state, so devices
+    /// can take turns on one bus, see [Sht31Pair](pair::Sht31Pair)
+    fn swap_device<J, K>(&mut self, other: &mut SHT31<Mode, J, K>) {
+        use core::mem::swap;
+
+        // Destructured in full so a new field can't be forgotten
+        let SHT31 {
+            mode,
+            i2c: _,
+            address,
+            accuracy,
+            unit,
+            heater,
+            heater_guard,
+            compensation,
+            plausibility_check,
+            health,
+            cache,
+            checksum,
+            checksum_policy,
+            checksum_failed,
+            clock: _,
+        } = self;
+        swap(mode, &mut other.mode);
+        swap(address, &mut other.address);
+        swap(accuracy, &mut other.accuracy);
+        swap(unit, &mut other.unit);
+        swap(heater, &mut other.heater);
+        swap(heater_guard, &mut other.heater_guard);
+        swap(compensation, &mut other.compensation);
+        swap(plausibility_check, &mut other.plausibility_check);
+        swap(health, &mut other.health);
+        swap(cache, &mut other.cache);
+        swap(checksum, &mut other.checksum);
+        swap(checksum_policy, &mut other.checksum_policy);
+        swap(checksum_failed, &mut other.checksum_failed);
+    }
+
     /// Change the sensor's temperature unit
     pub fn set_unit(&mut self, unit: TemperatureUnit) {
         self.unit = unit;
@@ -238,6 +714,56 @@ where
         self
     }
 
+    /// Compensate the die's self-heating, see [SelfHeating]
+    pub fn set_self_heating(&mut self, self_heating: SelfHeating) {
+        self.compensation.self_heating = Some(self_heating);
+    }
+
+    /// Compensate the die's self-heating, see [SelfHeating]
+    pub fn with_self_heating(mut self, self_heating: SelfHeating) -> Self {
+        self.set_self_heating(self_heating);
+        self
+    }
+
+    /// Calibration offsets added to every reading, the temperature offset is in Celsius
+    /// and the humidity offset in %RH
+    pub fn set_offsets(&mut self, temperature_offset: f32, humidity_offset: f32) {
+        let calibration = &mut self.compensation.calibration;
+        calibration.temperature.offset = temperature_offset;
+        calibration.humidity.offset = humidity_offset;
+    }
+
+    /// Calibration offsets added to every reading, the temperature offset is in Celsius
+    /// and the humidity offset in %RH
+    pub fn with_offsets(mut self, temperature_offset: f32, humidity_offset: f32) -> Self {
+        self.set_offsets(temperature_offset, humidity_offset);
+        self
+    }
+
+    /// Slope and offset calibration applied to every reading, see [Calibration]
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.compensation.calibration = calibration;
+    }
+
+    /// Slope and offset calibration applied to every reading, see [Calibration]
+    pub fn with_calibration(mut self, calibration: Calibration) -> Self {
+        self.set_calibration(calibration);
+        self
+    }
+
+    /// Reject physically impossible readings with [SHTError::ImplausibleReadingError],
+    /// a sensor returning all-zero or all-one data still passes the checksum
+    pub fn set_plausibility_check(&mut self, check: bool) {
+        self.plausibility_check = check;
+    }
+
+    /// Reject physically impossible readings with [SHTError::ImplausibleReadingError],
+    /// a sensor returning all-zero or all-one data still passes the checksum
+    pub fn with_plausibility_check(mut self) -> Self {
+        self.set_plausibility_check(true);
+        self
+    }
+
     /// Change the sensor's accuracy which also influences how long it takes to read
     pub fn set_accuracy(&mut self, accuracy: Accuracy) {
         self.accuracy = accuracy;
@@ -249,22 +775,80 @@ where
         self
     }
 
+    /// Change the sensor's I2C address
+    pub fn set_address(&mut self, address: DeviceAddr) {
+        self.address = address.into();
+    }
+
+    /// Drives the ADDR pin to select the given address and retargets the sensor to match,
+    /// the address is left untouched if the pin can't be set. Custom addresses can't be
+    /// selected through the pin
+    pub fn set_address_pin<P: OutputPin>(
+        &mut self,
+        pin: &mut P,
+        address: DeviceAddr,
+    ) -> Result<()> {
+        match address {
+            DeviceAddr::AD0 => pin.set_low(),
+            DeviceAddr::AD1 => pin.set_high(),
+            DeviceAddr::Custom(_) => return Err(SHTError::UnsupportedAddressError),
+        }
+        .map_err(|_| SHTError::PinError)?;
+
+        self.set_address(address);
+        Ok(())
+    }
+
+    /// Verify checksums with the given CRC-8 implementation instead of [SoftwareCrc]
+    pub fn set_checksum<CRC: Checksum>(&mut self) {
+        self.checksum = CRC::checksum;
+    }
+
+    /// Verify checksums with the given CRC-8 implementation instead of [SoftwareCrc]
+    pub fn with_checksum<CRC: Checksum>(mut self) -> Self {
+        self.set_checksum::<CRC>();
+        self
+    }
+
+    /// Change how readings with mismatching checksums are handled, the status and
+    /// serial number are always verified
+    pub fn set_checksum_policy(&mut self, policy: ChecksumPolicy) {
+        self.checksum_policy = policy;
+    }
+
+    /// Change how readings with mismatching checksums are handled, the status and
+    /// serial number are always verified
+    pub fn with_checksum_policy(mut self, policy: ChecksumPolicy) -> Self {
+        self.set_checksum_policy(policy);
+        self
+    }
+
+    /// Whether the last reading's checksum didn't match, only ever set under
+    /// [ChecksumPolicy::Lenient]
+    pub fn last_checksum_failed(&self) -> bool {
+        self.checksum_failed
+    }
+
     /// Change the sensor's I2C address
     pub fn with_address(mut self, address: DeviceAddr) -> Self {
-        self.address = address as u8;
+        self.set_address(address);
         self
     }
+}
 
-    /// Set the heater's heating state
+impl<Mode, I2C, C> SHT31<Mode, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Set the heater's heating state, checked against the [HeaterGuard] if there's one
     pub fn set_heating(&mut self, heating: bool) -> Result<()> {
-        self.heater = heating;
-        self.switch_heater()
+        self.switch_heater_guarded(heating)
     }
 
-    /// Enables the onboard heater
+    /// Enables the onboard heater, checked against the [HeaterGuard] if there's one
     pub fn with_heating(mut self) -> Result<Self> {
-        self.heater = true;
-        self.switch_heater()?;
+        self.switch_heater_guarded(true)?;
         Ok(self)
     }
 
@@ -274,53 +858,127 @@ where
 
     /// Switch the heater on or off
     fn switch_heater(&mut self) -> Result<()> {
-        let lsb = if self.heater { 0x6D } else { 0x66 };
+        let command = if self.heater {
+            Command::HeaterOn
+        } else {
+            Command::HeaterOff
+        };
 
-        self.i2c_write(&[0x30, lsb])
+        self.send_command(command)
     }
 
     /// Cancel the currently running command, this is necessary for when attempting to transition
     /// between single shot and periodic
     pub fn break_command(&mut self) -> Result<()> {
-        self.i2c_write(&[0x30, 0x93])
+        self.send_command(Command::BreakCommand)
     }
 
-    /// Trigger a soft reset
+    /// Trigger a soft reset, the sensor won't accept commands for a while after,
+    /// see [SHT31::soft_reset_and_wait]. The reset turns the heater off
     pub fn soft_reset(&mut self) -> Result<()> {
-        self.i2c_write(&[0x30, 0xA2])
+        self.send_command(Command::SoftReset)?;
+        self.heater = false;
+        self.record_heater();
+        Ok(())
+    }
+
+    /// Trigger a soft reset and wait until the sensor accepts commands again
+    pub fn soft_reset_and_wait<D: DelayNs>(&mut self, delay: &mut D) -> Result<()> {
+        self.soft_reset()?;
+        delay.delay_us(RESET_DELAY_US);
+        Ok(())
+    }
+
+    /// Pulses the nRESET pin and waits until the sensor is ready again, this recovers
+    /// the sensor in cases where a soft reset won't. The sensor is left idle
+    pub fn hard_reset<P: OutputPin, D: DelayNs>(
+        mut self,
+        reset: &mut P,
+        delay: &mut D,
+    ) -> TransitionResult<SHT31<Idle, I2C, C>, Self> {
+        let result = pulse_reset(reset, delay);
+        if result.is_ok() {
+            self.heater = false;
+            self.record_heater();
+        }
+        self.transition(result, Idle)
     }
 
-    /// Triggers an I2C general reset, keep in mind that this will reset all
-    /// I2C devices connected to this line
-    pub fn reset(&mut self) -> Result<()> {
-        self.i2c_write(&[0x00, 0x06])
+    /// Triggers an I2C general call reset, keep in mind that this will reset all
+    /// I2C devices connected to this line that support it. The [GeneralCall] acknowledgment
+    /// makes sure this doesn't happen by accident
+    pub fn general_call_reset(&mut self, _acknowledgment: GeneralCall) -> Result<()> {
+        let result = ll::write(&mut self.i2c, GENERAL_CALL_ADDRESS, &[0x06]);
+        self.track(result)
     }
 
     /// Read the sensors status
     pub fn status(&mut self) -> Result<Status> {
         let mut buffer = [0; 3];
 
-        self.i2c_write_read(&[0xF3, 0x2D], &mut buffer)?;
-
-        // Verify data
-        let calculated = calculate_checksum(&Crc::<u8>::new(&CRC_ALGORITHM), buffer[0], buffer[1]);
-        if calculated != buffer[2] {
-            return Err(SHTError::InvalidStatusChecksumError {
-                bytes_start: buffer[0],
-                bytes_end: buffer[1],
-                expected_checksum: buffer[2],
-                calculated_checksum: calculated,
-            });
+        self.command_and_read(Command::ReadStatus, &mut buffer)?;
+
+        let status = ll::verify_status(self.checksum, buffer).map(Status::from_bytes);
+        self.track(status)
+    }
+
+    /// Polls the status every millisecond until the last command is reported as processed,
+    /// giving up with [SHTError::CommandTimeoutError] after `max_retries` polls
+    pub fn wait_for_command_processed<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+        max_retries: u8,
+    ) -> Result<()> {
+        for _ in 0..max_retries {
+            if self.status()?.last_command_processed {
+                return Ok(());
+            }
+            delay.delay_ms(1);
         }
+        self.track(Err(SHTError::CommandTimeoutError))
+    }
+
+    /// Checks that the sensor acknowledges a status read with a valid checksum
+    pub fn probe(&mut self) -> Result<()> {
+        self.status().map(|_| ())
+    }
+
+    /// Whether the sensor responds to [SHT31::probe]
+    pub fn is_connected(&mut self) -> bool {
+        self.probe().is_ok()
+    }
+
+    /// Read the sensor's unique serial number
+    pub fn serial_number(&mut self) -> Result<u32> {
+        let mut buffer = [0; 6];
 
-        Ok(Status::from_bytes(u16::from_be_bytes([
-            buffer[0], buffer[1],
-        ])))
+        self.command_and_read(Command::ReadSerialNumber, &mut buffer)?;
+
+        let serial_number = ll::verify_serial_number(self.checksum, buffer);
+        self.track(serial_number)
     }
 
     /// Clear all status registers
     pub fn clear_status(&mut self) -> Result<()> {
-        self.i2c_write(&[0x30, 0x41])
+        self.send_command(Command::ClearStatus)
+    }
+
+    /// Power-up sequence, waits until the sensor is ready, checks that it responds and
+    /// clears its status. Returns the status as it was before clearing so the reset
+    /// flag can still be checked, the status is cleared in the same transaction even if
+    /// its checksum turns out to be wrong
+    pub fn init<D: DelayNs>(&mut self, delay: &mut D) -> Result<Status> {
+        delay.delay_ms(POWER_UP_DELAY_MS);
+
+        let mut buffer = [0; 3];
+        self.i2c_transaction(&mut [
+            Operation::Write(&Command::ReadStatus.to_bytes()),
+            Operation::Read(&mut buffer),
+            Operation::Write(&Command::ClearStatus.to_bytes()),
+        ])?;
+
+        let status = ll::verify_status(self.checksum, buffer).map(Status::from_bytes);
+        self.track(status)
     }
 
     /// Consumes the instance and returns the i2c
@@ -329,47 +987,32 @@ where
     }
 
     fn i2c_write(&mut self, bytes: &[u8]) -> Result<()> {
-        match self.i2c.write(self.address, bytes) {
-            Ok(res) => Ok(res),
-            Err(_) => Err(SHTError::WriteI2CError),
-        }
+        let result = ll::write(&mut self.i2c, self.address, bytes);
+        self.track(result)
     }
 
     fn i2c_read(&mut self, buffer: &mut [u8]) -> Result<()> {
-        match self.i2c.read(self.address, buffer) {
-            Ok(res) => Ok(res),
-            Err(_) => Err(SHTError::ReadI2CError),
-        }
+        let result = ll::read(&mut self.i2c, self.address, buffer);
+        self.track(result)
     }
 
-    fn i2c_write_read(&mut self, bytes: &[u8], buffer: &mut [u8]) -> Result<()> {
-        match self.i2c.write_read(self.address, bytes, buffer) {
+    /// Same as [SHT31::i2c_read] but data that isn't ready yet would block
+    fn i2c_read_nb(&mut self, buffer: &mut [u8]) -> nb::Result<(), SHTError> {
+        match self.i2c_read(buffer) {
             Ok(res) => Ok(res),
-            Err(_) => Err(SHTError::WriteReadI2CError),
+            Err(SHTError::DataNotReadyError) => Err(nb::Error::WouldBlock),
+            Err(err) => Err(nb::Error::Other(err)),
         }
     }
 
-    fn process_data(&self, buffer: [u8; 6]) -> Result<Reading> {
-        Self::verify_data(buffer)?;
-
-        let raw_temp = i16::from_be_bytes([buffer[0], buffer[1]]) as f32;
-
-        let (sub, mul) = match self.unit {
-            TemperatureUnit::Celsius => CELSIUS_PAIR,
-            TemperatureUnit::Fahrenheit => FAHRENHEIT_PAIR,
-        };
-
-        let pre_sub = mul * (raw_temp / CONVERSION_DENOM);
-
-        let temperature = pre_sub - sub;
-
-        let raw_humidity = i16::from_be_bytes([buffer[0], buffer[1]]) as f32;
-        let humidity = 100f32 * raw_humidity / CONVERSION_DENOM;
+    fn i2c_write_read(&mut self, bytes: &[u8], buffer: &mut [u8]) -> Result<()> {
+        let result = ll::write_read(&mut self.i2c, self.address, bytes, buffer);
+        self.track(result)
+    }
 
-        Ok(Reading {
-            temperature,
-            humidity,
-        })
+    fn i2c_transaction(&mut self, operations: &mut [Operation]) -> Result<()> {
+        let result = ll::transaction(&mut self.i2c, self.address, operations);
+        self.track(result)
     }
 }
 
@@ -378,26 +1021,41 @@ mod test {
     extern crate alloc;
     use super::*;
     use crate::prelude::*;
+    use crate::test_utils::{self, not_ready};
+    use alloc::format;
     use alloc::vec;
     use alloc::vec::Vec;
+    use core::num::NonZeroU16;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
     use embedded_hal_mock::common::Generic;
-    use embedded_hal_mock::eh1::delay::CheckedDelay;
+    use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+    use embedded_hal_mock::eh1::digital::{
+        Mock as PinMock, State as PinState, Transaction as PinTransaction,
+    };
     use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
     use rstest::rstest;
 
-    impl SHT31<SingleShot, Generic<Transaction>> {
+    const ADDR: u8 = 0x44;
+
+    impl<State> SHT31<SingleShot<State>, Generic<Transaction>> {
         fn done(mut self) {
             self.i2c.done()
         }
     }
 
-    impl SHT31<Periodic, Generic<Transaction>> {
+    impl SHT31<ClockStretch, Generic<Transaction>> {
         fn done(mut self) {
             self.i2c.done()
         }
     }
 
-    impl SHT31<SimpleSingleShot<CheckedDelay>, Generic<Transaction>> {
+    impl<State> SHT31<Periodic<State>, Generic<Transaction>> {
+        fn done(mut self) {
+            self.i2c.done()
+        }
+    }
+
+    impl SHT31<SimpleSingleShot<CheckedDelay>, Generic<Transaction>> {
         fn done(mut self) {
             self.i2c.done();
             self.mode.delay.done();
@@ -405,33 +1063,272 @@ mod test {
     }
 
     #[test]
-    fn reading() {
-        let buffer = [98, 153, 188, 98, 32, 139];
+    fn custom_checksum() {
+        struct Parity;
 
-        assert!(verify_reading(buffer).is_ok());
+        impl Checksum for Parity {
+            fn checksum(word: [u8; 2]) -> u8 {
+                word[0] ^ word[1]
+            }
+        }
 
-        let corrupt_temperature = [98, 153, 180, 98, 32, 139];
+        let expectations = [
+            Transaction::write(ADDR, vec![0x24, 0x00]),
+            Transaction::read(ADDR, vec![98, 153, 98 ^ 153, 98, 32, 98 ^ 32]),
+        ];
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new())
+            .with_checksum::<Parity>()
+            .measure()
+            .unwrap();
+        assert_eq!(sht31.read().unwrap().temperature, 72.32318);
 
+        sht31.done();
+    }
+
+    #[rstest]
+    #[case(ChecksumPolicy::Strict, false, 1)]
+    #[case(ChecksumPolicy::Lenient, true, 1)]
+    #[case(ChecksumPolicy::Skip, true, 0)]
+    fn checksum_policy(#[case] policy: ChecksumPolicy, #[case] read: bool, #[case] counted: u32) {
+        let expectations = [
+            Transaction::write(ADDR, vec![0x24, 0x00]),
+            Transaction::read(ADDR, vec![98, 153, 0, 98, 32, 139]),
+        ];
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new())
+            .with_checksum_policy(policy)
+            .measure()
+            .unwrap();
+
+        assert_eq!(sht31.read().is_ok(), read);
         assert_eq!(
-            verify_reading(corrupt_temperature).err().unwrap(),
-            SHTError::InvalidTemperatureChecksumError {
-                bytes_start: 98,
-                bytes_end: 153,
-                expected_checksum: 180,
-                calculated_checksum: 188
+            sht31.last_checksum_failed(),
+            policy == ChecksumPolicy::Lenient
+        );
+        assert_eq!(sht31.health().crc_errors, counted);
+
+        sht31.done();
+    }
+
+    #[test]
+    fn address() {
+        assert_eq!(u8::from(DeviceAddr::AD0), 0x44);
+        assert_eq!(u8::from(DeviceAddr::AD1), 0x45);
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&[]), SingleShot::new())
+            .with_address(DeviceAddr::Custom(0x70));
+        assert_eq!(sht31.address(), 0x70);
+
+        sht31.set_address(DeviceAddr::AD1);
+        assert_eq!(sht31.address(), 0x45);
+
+        sht31.done();
+    }
+
+    #[test]
+    fn address_pin() {
+        let mut pin = PinMock::new(&[
+            PinTransaction::set(PinState::High),
+            PinTransaction::set(PinState::Low),
+        ]);
+        let mut sht31 = SHT31::single_shot(Mock::new(&[]), SingleShot::new());
+
+        sht31.set_address_pin(&mut pin, DeviceAddr::AD1).unwrap();
+        assert_eq!(sht31.address(), 0x45);
+        sht31.set_address_pin(&mut pin, DeviceAddr::AD0).unwrap();
+        assert_eq!(sht31.address(), 0x44);
+        assert_eq!(
+            sht31
+                .set_address_pin(&mut pin, DeviceAddr::Custom(0x70))
+                .err()
+                .unwrap(),
+            SHTError::UnsupportedAddressError
+        );
+        assert_eq!(sht31.address(), 0x44);
+
+        sht31.done();
+        pin.done();
+    }
+
+    #[test]
+    fn autodetect() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0xF3, 0x2D], vec![0; 3])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            Transaction::write_read(0x45, vec![0xF3, 0x2D], vec![0x80, 0x10, 0xE1]),
+        ];
+        let sht31 = SHT31::autodetect(Mock::new(&expectations), CheckedDelay::new([])).unwrap();
+        assert_eq!(sht31.address(), 0x45);
+        sht31.done();
+
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0xF3, 0x2D], vec![0; 3])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            Transaction::write_read(0x45, vec![0xF3, 0x2D], vec![0; 3])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+        ];
+        let err = SHT31::autodetect(Mock::new(&expectations), CheckedDelay::new([]))
+            .err()
+            .unwrap();
+        assert_eq!(err.error, SHTError::DeviceNotFoundError);
+        err.sensor.done();
+    }
+
+    #[test]
+    fn wait_for_command_processed() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0xF3, 0x2D], vec![0x00, 0x02, 0xE3]),
+            Transaction::write_read(ADDR, vec![0xF3, 0x2D], vec![0x00, 0x00, 0x81]),
+            Transaction::write_read(ADDR, vec![0xF3, 0x2D], vec![0x00, 0x02, 0xE3]),
+        ];
+        let mut delay =
+            CheckedDelay::new(&[DelayTransaction::delay_ms(1), DelayTransaction::delay_ms(1)]);
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+
+        sht31.wait_for_command_processed(&mut delay, 2).unwrap();
+        assert_eq!(
+            sht31
+                .wait_for_command_processed(&mut delay, 1)
+                .err()
+                .unwrap(),
+            SHTError::CommandTimeoutError
+        );
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[test]
+    fn probe() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0xF3, 0x2D], vec![0x80, 0x10, 0xE1]),
+            Transaction::write_read(ADDR, vec![0xF3, 0x2D], vec![0x80, 0x10, 0x00]),
+            Transaction::write_read(ADDR, vec![0xF3, 0x2D], vec![0; 3])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+        ];
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+
+        assert!(sht31.probe().is_ok());
+        assert_eq!(
+            sht31.probe().err().unwrap(),
+            SHTError::InvalidStatusChecksumError {
+                bytes_start: 0x80,
+                bytes_end: 0x10,
+                expected_checksum: 0x00,
+                calculated_checksum: 0xE1
             }
         );
+        assert!(!sht31.is_connected());
+
+        sht31.done();
+    }
+
+    #[test]
+    fn serial_number() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0x37, 0x80], vec![98, 153, 188, 98, 32, 139]),
+            Transaction::write_read(ADDR, vec![0x37, 0x80], vec![98, 153, 188, 98, 32, 0]),
+        ];
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
 
-        let corrupt_humidity = [98, 153, 188, 98, 32, 180];
+        assert_eq!(sht31.serial_number().unwrap(), 0x62996220);
         assert_eq!(
-            verify_reading(corrupt_humidity).err().unwrap(),
-            SHTError::InvalidHumidityChecksumError {
+            sht31.serial_number().err().unwrap(),
+            SHTError::InvalidSerialNumberChecksumError {
                 bytes_start: 98,
                 bytes_end: 32,
-                expected_checksum: 180,
+                expected_checksum: 0,
                 calculated_checksum: 139
             }
         );
+
+        sht31.done();
+    }
+
+    #[rstest]
+    #[case(Accuracy::High, 15.5)]
+    #[case(Accuracy::Medium, 6.5)]
+    #[case(Accuracy::Low, 4.5)]
+    fn max_duration(#[case] accuracy: Accuracy, #[case] ms: f32) {
+        assert_eq!(accuracy.max_duration_ms(), ms);
+    }
+
+    #[rstest]
+    #[case(0.2, 0.25, Some(Accuracy::Low))]
+    #[case(0.1, 0.2, Some(Accuracy::Medium))]
+    #[case(0.05, 0.1, Some(Accuracy::High))]
+    #[case(0.01, 0.5, None)]
+    fn fastest_within(
+        #[case] temperature: f32,
+        #[case] humidity: f32,
+        #[case] accuracy: Option<Accuracy>,
+    ) {
+        assert_eq!(Accuracy::fastest_within(temperature, humidity), accuracy);
+    }
+
+    #[test]
+    fn display() {
+        let reading = Reading {
+            temperature: 23.44,
+            humidity: 41.25,
+            unit: TemperatureUnit::Celsius,
+        };
+        assert_eq!(format!("{reading}"), "23.4°C 41.2%RH");
+
+        assert_eq!(
+            format!("{}", Status::from_bytes(0x2C00)),
+            "alerts: T,RH; heater: on"
+        );
+        assert_eq!(
+            format!("{}", Status::from_bytes(0x0000)),
+            "alerts: none; heater: off"
+        );
+    }
+
+    #[test]
+    fn reading_comparison() {
+        let fahrenheit = Reading {
+            temperature: 72.32318,
+            humidity: 38.33066,
+            unit: TemperatureUnit::Fahrenheit,
+        };
+        let celsius = Reading {
+            temperature: 22.4,
+            humidity: 38.3,
+            unit: TemperatureUnit::Celsius,
+        };
+
+        assert_eq!(fahrenheit, fahrenheit);
+        assert_ne!(fahrenheit, celsius);
+        assert!(celsius.approx_eq(&fahrenheit, 0.01, 0.05));
+        assert!(!celsius.approx_eq(&fahrenheit, 0.001, 0.05));
+        assert!(!fahrenheit.approx_eq(&celsius, 0.01, 0.01));
+    }
+
+    #[test]
+    fn reading_arithmetic() {
+        let a = Reading {
+            temperature: 20f32,
+            humidity: 40f32,
+            unit: TemperatureUnit::Celsius,
+        };
+        let b = Reading {
+            temperature: 75.2,
+            humidity: 50f32,
+            unit: TemperatureUnit::Fahrenheit,
+        };
+
+        // 75.2 °F is 24 °C
+        let mean = (a + b) / 2f32;
+        assert!((mean.temperature - 22f32).abs() < 0.001);
+        assert_eq!(mean.humidity, 45f32);
+        assert_eq!(mean.unit, TemperatureUnit::Celsius);
+
+        let difference = b - a;
+        assert!((difference.temperature - 7.2).abs() < 0.001);
+        assert_eq!(difference.humidity, 10f32);
+        assert_eq!(difference.unit, TemperatureUnit::Fahrenheit);
+
+        assert_eq!((a * 0.5).temperature, 10f32);
     }
 
     #[test]
@@ -444,15 +1341,22 @@ mod test {
         assert!(status.system_reset);
         assert!(status.last_command_processed);
         assert!(!status.checksum_failed);
+        assert_eq!(status.raw, 0x8010);
+        assert_eq!(status.to_bytes(), 0x8010);
+
+        // Reserved bits survive while flags are re-encoded
+        let mut status = Status::from_bytes(0xAC33);
+        assert_eq!(status.to_bytes(), 0xAC33);
+        status.heater_on = false;
+        status.last_command_processed = true;
+        assert_eq!(status.to_bytes(), 0x8C31);
     }
 
+    /// Spells the opcode out rather than going through [Command] so opcodes are checked
     fn single_shot_expectations(msb: u8, lsb: u8) -> [Transaction; 2] {
         [
-            Transaction::write(DeviceAddr::AD0 as u8, Vec::from(&[msb, lsb])),
-            Transaction::read(
-                DeviceAddr::AD0 as u8,
-                Vec::from(&[98, 153, 188, 98, 32, 139]),
-            ),
+            Transaction::write(ADDR, Vec::from(&[msb, lsb])),
+            Transaction::read(ADDR, Vec::from(&[98, 153, 188, 98, 32, 139])),
         ]
     }
 
@@ -465,7 +1369,87 @@ mod test {
 
         let mut sht31 = SHT31::new(i2c, CheckedDelay::new([])).with_accuracy(accuracy);
         let reading = sht31.read().unwrap();
-        assert_eq!(reading.humidity, 38.515297);
+        assert_eq!(reading.humidity, 38.33066);
+        assert_eq!(reading.temperature, 72.32318);
+
+        sht31.done();
+    }
+
+    #[test]
+    fn simple_single_shot_retries() {
+        let nack = || not_ready(ADDR);
+        let expectations = [
+            // Succeeds on the second attempt
+            Transaction::write(ADDR, vec![0x2C, 0x06]),
+            nack(),
+            Transaction::read(ADDR, vec![98, 153, 188, 98, 32, 139]),
+            // Times out
+            Transaction::write(ADDR, vec![0x2C, 0x06]),
+            nack(),
+            nack(),
+            // Corrupt data is surfaced
+            Transaction::write(ADDR, vec![0x2C, 0x06]),
+            nack(),
+            Transaction::read(ADDR, vec![98, 153, 0, 98, 32, 139]),
+            // Bus faults aren't retried
+            Transaction::write(ADDR, vec![0x2C, 0x06]),
+            Transaction::read(ADDR, vec![0; 6]).with_error(ErrorKind::Bus),
+        ];
+        let delay = CheckedDelay::new(&[
+            DelayTransaction::delay_ms(100),
+            DelayTransaction::delay_ms(100),
+            DelayTransaction::delay_ms(100),
+        ]);
+
+        let mut sht31 = SHT31::new(Mock::new(&expectations), delay);
+        sht31.mode.set_max_retries(2);
+
+        assert!(sht31.read().is_ok());
+        assert_eq!(
+            sht31.read().err().unwrap(),
+            SHTError::ReadingTimeoutError { attempts: 2 }
+        );
+        assert!(matches!(
+            sht31.read().err().unwrap(),
+            SHTError::InvalidTemperatureChecksumError { .. }
+        ));
+        assert_eq!(sht31.read().err().unwrap(), SHTError::ReadI2CError);
+
+        sht31.done();
+    }
+
+    #[test]
+    fn simple_single_shot_no_retries() {
+        let expectations = [Transaction::write(ADDR, vec![0x2C, 0x06])];
+
+        let mut sht31 = SHT31::simple_single_shot(
+            Mock::new(&expectations),
+            SimpleSingleShot::new(CheckedDelay::new([])).with_max_retries(0),
+        );
+        assert_eq!(
+            sht31.read().err().unwrap(),
+            SHTError::ReadingTimeoutError { attempts: 0 }
+        );
+
+        sht31.done();
+    }
+
+    #[rstest]
+    #[case(0x10, Accuracy::Low)]
+    #[case(0x0D, Accuracy::Medium)]
+    #[case(0x06, Accuracy::High)]
+    fn clock_stretch(#[case] lsb: u8, #[case] accuracy: Accuracy) {
+        let [command, read] = single_shot_expectations(0x2C, lsb);
+        let i2c = Mock::new(&[
+            Transaction::transaction_start(ADDR),
+            command,
+            read,
+            Transaction::transaction_end(ADDR),
+        ]);
+
+        let mut sht31 = SHT31::clock_stretch(i2c, ClockStretch::new()).with_accuracy(accuracy);
+        let reading = sht31.read().unwrap();
+        assert_eq!(reading.humidity, 38.33066);
         assert_eq!(reading.temperature, 72.32318);
 
         sht31.done();
@@ -478,10 +1462,134 @@ mod test {
     fn single_shot(#[case] lsb: u8, #[case] accuracy: Accuracy) {
         let i2c = Mock::new(&single_shot_expectations(0x24, lsb));
 
-        let mut sht31 = SHT31::single_shot(i2c, SingleShot::new()).with_accuracy(accuracy);
-        sht31.measure().unwrap();
+        let mut sht31 = SHT31::single_shot(i2c, SingleShot::new())
+            .with_accuracy(accuracy)
+            .measure()
+            .unwrap();
         let reading = sht31.read().unwrap();
-        assert_eq!(reading.humidity, 38.515297);
+        assert_eq!(reading.humidity, 38.33066);
+        assert_eq!(reading.temperature, 72.32318);
+
+        sht31.done()
+    }
+
+    #[rstest]
+    #[case(0x16, Accuracy::Low)]
+    #[case(0x0B, Accuracy::Medium)]
+    #[case(0x00, Accuracy::High)]
+    fn single_shot_measure_and_wait(#[case] lsb: u8, #[case] accuracy: Accuracy) {
+        let i2c = Mock::new(&single_shot_expectations(0x24, lsb));
+        let mut delay =
+            CheckedDelay::new(&[DelayTransaction::delay_us(accuracy.max_duration_us())]);
+
+        let mut sht31 = SHT31::single_shot(i2c, SingleShot::new()).with_accuracy(accuracy);
+        let reading = sht31.measure_and_wait(&mut delay).unwrap();
+        assert_eq!(reading.temperature, 72.32318);
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[test]
+    fn single_shot_read_low_power() {
+        let i2c = Mock::new(&single_shot_expectations(0x24, 0x16));
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(4_500)]);
+
+        let mut sht31 = SHT31::single_shot(i2c, SingleShot::new()).with_accuracy(Accuracy::High);
+        let reading = sht31.read_low_power(&mut delay).unwrap();
+        assert_eq!(reading.temperature, 72.32318);
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[test]
+    fn single_shot_read_averaged() {
+        let [first_command, first] = test_utils::single_shot_expectations(
+            ADDR,
+            Accuracy::Low,
+            false,
+            test_utils::frame(20f32, 40f32),
+        );
+        let [second_command, second] = test_utils::single_shot_expectations(
+            ADDR,
+            Accuracy::Low,
+            false,
+            test_utils::frame(22f32, 50f32),
+        );
+        let i2c = Mock::new(&[first_command, first, second_command, second]);
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_us(4_500),
+            DelayTransaction::delay_us(4_500),
+        ]);
+
+        let mut sht31 = SHT31::single_shot(i2c, SingleShot::new())
+            .with_accuracy(Accuracy::Low)
+            .with_unit(TemperatureUnit::Celsius);
+        let averaged = sht31
+            .read_averaged(NonZeroU16::new(2).unwrap(), &mut delay)
+            .unwrap();
+        assert!(averaged.mean.approx_eq(
+            &Reading {
+                temperature: 21f32,
+                humidity: 45f32,
+                unit: TemperatureUnit::Celsius,
+            },
+            0.01,
+            0.01
+        ));
+        assert!((averaged.humidity.max - averaged.humidity.min - 10f32).abs() < 0.01);
+        assert!((averaged.temperature.variance - 1f32).abs() < 0.01);
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[rstest]
+    #[case([0, 0, 129, 0, 0, 129], false)]
+    #[case([255, 255, 172, 255, 255, 172], false)]
+    #[case([98, 153, 188, 0, 0, 129], false)]
+    #[case([98, 153, 188, 98, 32, 139], true)]
+    fn plausibility_check(#[case] data: [u8; 6], #[case] plausible: bool) {
+        let expectations = [
+            Transaction::write(ADDR, vec![0x24, 0x00]),
+            Transaction::read(ADDR, data.to_vec()),
+        ];
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new())
+            .with_plausibility_check()
+            .measure()
+            .unwrap();
+        let reading = sht31.read();
+        if plausible {
+            assert!(reading.is_ok());
+        } else {
+            assert_eq!(reading.err().unwrap(), SHTError::ImplausibleReadingError);
+        }
+
+        sht31.done();
+    }
+
+    #[test]
+    fn single_shot_nb() {
+        let expectations = [
+            Transaction::write(ADDR, vec![0x24, 0x00]),
+            Transaction::read(ADDR, vec![0; 6])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            Transaction::read(ADDR, vec![0; 6]).with_error(ErrorKind::Bus),
+            Transaction::read(ADDR, vec![98, 153, 188, 98, 32, 139]),
+        ];
+        let i2c = Mock::new(&expectations);
+
+        let mut sht31 = SHT31::single_shot(i2c, SingleShot::new())
+            .measure()
+            .unwrap();
+        assert_eq!(sht31.read_nb().err().unwrap(), nb::Error::WouldBlock);
+        assert_eq!(
+            sht31.read_nb().err().unwrap(),
+            nb::Error::Other(SHTError::ReadI2CError)
+        );
+        let reading = nb::block!(sht31.read_nb()).unwrap();
         assert_eq!(reading.temperature, 72.32318);
 
         sht31.done()
@@ -512,44 +1620,458 @@ mod test {
         #[case] mps: MPS,
     ) {
         let expectations = [
-            Transaction::write(DeviceAddr::AD0 as u8, vec![msb, lsb]),
-            Transaction::write_read(
-                DeviceAddr::AD0 as u8,
-                vec![0xE0, 0x00],
-                vec![98, 153, 188, 98, 32, 139],
-            ),
+            Transaction::write(ADDR, vec![msb, lsb]),
+            Transaction::write(ADDR, vec![0xE0, 0x00]),
+            Transaction::read(ADDR, vec![98, 153, 188, 98, 32, 139]),
         ];
         let i2c = Mock::new(&expectations);
 
         let mut periodic = Periodic::new().with_mps(mps);
-        if art {
-            periodic.set_art();
-        }
+        periodic.set_art(art);
 
-        let mut sht31 = SHT31::periodic(i2c, periodic).with_accuracy(accuracy);
-        sht31.measure().unwrap();
+        let mut sht31 = SHT31::periodic(i2c, periodic)
+            .with_accuracy(accuracy)
+            .measure()
+            .unwrap();
         let reading = sht31.read().unwrap();
-        assert_eq!(reading.humidity, 38.515297);
+        assert_eq!(reading.humidity, 38.33066);
         assert_eq!(reading.temperature, 72.32318);
 
         sht31.done();
     }
 
+    #[test]
+    fn periodic_no_new_data() {
+        let expectations = [
+            Transaction::write(ADDR, vec![0x21, 0x30]),
+            Transaction::write(ADDR, vec![0xE0, 0x00]),
+            Transaction::read(ADDR, vec![0; 6])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+        ];
+        let i2c = Mock::new(&expectations);
+
+        let mut sht31 = SHT31::periodic(i2c, Periodic::new()).measure().unwrap();
+        assert_eq!(sht31.read().err().unwrap(), SHTError::DataNotReadyError);
+
+        sht31.done();
+    }
+
+    #[test]
+    fn periodic_missing_sensor() {
+        let expectations = [
+            Transaction::write(ADDR, vec![0x21, 0x30]),
+            Transaction::write(ADDR, vec![0xE0, 0x00])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+        ];
+        let i2c = Mock::new(&expectations);
+
+        let mut sht31 = SHT31::periodic(i2c, Periodic::new()).measure().unwrap();
+        assert_eq!(sht31.read().err().unwrap(), SHTError::WriteI2CError);
+
+        sht31.done();
+    }
+
+    #[test]
+    fn periodic_fetch_latest_or_wait() {
+        let nack = || {
+            [
+                Transaction::write(ADDR, vec![0xE0, 0x00]),
+                Transaction::read(ADDR, vec![0; 6])
+                    .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            ]
+        };
+        let mut expectations = vec![Transaction::write(ADDR, vec![0x21, 0x30])];
+        // Fresh data on the second fetch
+        expectations.extend(nack());
+        expectations.push(Transaction::write(ADDR, vec![0xE0, 0x00]));
+        expectations.push(Transaction::read(ADDR, vec![98, 153, 188, 98, 32, 139]));
+        // Times out
+        expectations.extend(nack());
+        expectations.extend(nack());
+        expectations.extend(nack());
+        let i2c = Mock::new(&expectations);
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_ms(10),
+            DelayTransaction::delay_ms(10),
+            DelayTransaction::delay_ms(5),
+        ]);
+
+        let mut sht31 = SHT31::periodic(i2c, Periodic::new()).measure().unwrap();
+        let reading = sht31.fetch_latest_or_wait(&mut delay, 15).unwrap();
+        assert_eq!(reading.temperature, 72.32318);
+        assert_eq!(
+            sht31.fetch_latest_or_wait(&mut delay, 15).err().unwrap(),
+            SHTError::FetchTimeoutError
+        );
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[test]
+    fn periodic_run() {
+        use core::ops::ControlFlow;
+
+        let mut expectations = vec![Transaction::write(ADDR, vec![0x27, 0x37])];
+        expectations.extend(test_utils::fetch_expectations(
+            ADDR,
+            [98, 153, 188, 98, 32, 139],
+        ));
+        // The second sample is a little late
+        expectations.push(Transaction::write(ADDR, vec![0xE0, 0x00]));
+        expectations.push(not_ready(ADDR));
+        expectations.extend(test_utils::fetch_expectations(
+            ADDR,
+            [98, 153, 188, 98, 32, 139],
+        ));
+        let i2c = Mock::new(&expectations);
+        // Three quarters of the 100 ms period
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_us(75_000),
+            DelayTransaction::delay_us(75_000),
+            DelayTransaction::delay_ms(10),
+        ]);
+
+        let mut sht31 = SHT31::periodic(i2c, Periodic::new().with_mps(MPS::X10))
+            .measure()
+            .unwrap();
+        let mut samples = 0;
+        let last = sht31.run(&mut delay, |reading| {
+            samples += 1;
+            match samples {
+                2 => ControlFlow::Break(reading),
+                _ => ControlFlow::Continue(()),
+            }
+        });
+        assert_eq!(last.unwrap().temperature, 72.32318);
+        assert_eq!(samples, 2);
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[rstest]
+    // Words above 0x7FFF used to decode as negative
+    #[case(0xFFFF, 0x4000, 130f32, 25.000381)]
+    // Humidity used to be decoded from the temperature word
+    #[case(0x0000, 0xFFFF, -45f32, 100f32)]
+    fn decodes_raw_words(
+        #[case] raw_temperature: u16,
+        #[case] raw_humidity: u16,
+        #[case] temperature: f32,
+        #[case] humidity: f32,
+    ) {
+        let mut sht31 = SHT31::single_shot(Mock::new(&[]), SingleShot::new())
+            .with_unit(TemperatureUnit::Celsius);
+
+        let frame = test_utils::raw_frame(raw_temperature, raw_humidity);
+        let reading = sht31.process_data(frame).unwrap();
+        assert_eq!(reading.temperature, temperature);
+        assert_eq!(reading.humidity, humidity);
+
+        sht31.done();
+    }
+
+    #[test]
+    fn soft_reset_and_wait() {
+        let expectations = [
+            Transaction::write(ADDR, vec![0x30, 0x6D]),
+            Transaction::write(ADDR, vec![0x30, 0xA2]),
+        ];
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(1500)]);
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+        sht31.set_heating(true).unwrap();
+        sht31.soft_reset_and_wait(&mut delay).unwrap();
+        assert!(!sht31.heater);
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[test]
+    fn soft_reset_turns_heater_off() {
+        let expectations = [
+            Transaction::write(ADDR, vec![0x30, 0x6D]),
+            Transaction::write(ADDR, vec![0x30, 0xA2]).with_error(ErrorKind::Other),
+            Transaction::write(ADDR, vec![0x30, 0xA2]),
+        ];
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+        sht31.set_heating(true).unwrap();
+        assert!(sht31.soft_reset().is_err());
+        assert!(sht31.heater);
+        sht31.soft_reset().unwrap();
+        assert!(!sht31.heater);
+
+        sht31.done();
+    }
+
+    #[test]
+    fn init() {
+        let expectations = [
+            Transaction::transaction_start(ADDR),
+            Transaction::write(ADDR, vec![0xF3, 0x2D]),
+            Transaction::read(ADDR, vec![0x80, 0x10, 0xE1]),
+            Transaction::write(ADDR, vec![0x30, 0x41]),
+            Transaction::transaction_end(ADDR),
+        ];
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(1)]);
+
+        let mut sht31 = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+        let status = sht31.init(&mut delay).unwrap();
+        assert!(status.system_reset);
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[test]
+    fn periodic_rearm_after_reset() {
+        let nack = || {
+            [
+                Transaction::write(ADDR, vec![0xE0, 0x00]),
+                Transaction::read(ADDR, vec![0; 6])
+                    .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            ]
+        };
+        let mut expectations = vec![Transaction::write(ADDR, vec![0x21, 0x30])];
+        // No reset, just no new data yet
+        expectations.extend(nack());
+        expectations.push(Transaction::write_read(
+            ADDR,
+            vec![0xF3, 0x2D],
+            vec![0x00, 0x00, 0x81],
+        ));
+        // Brown-out
+        expectations.extend(nack());
+        expectations.push(Transaction::write_read(
+            ADDR,
+            vec![0xF3, 0x2D],
+            vec![0x00, 0x10, 0xC2],
+        ));
+        expectations.push(Transaction::write(ADDR, vec![0x30, 0x41]));
+        expectations.push(Transaction::write(ADDR, vec![0x21, 0x30]));
+        let i2c = Mock::new(&expectations);
+
+        let mut sht31 = SHT31::periodic(i2c, Periodic::new().with_rearm_on_failure())
+            .measure()
+            .unwrap();
+        assert_eq!(sht31.read().err().unwrap(), SHTError::DataNotReadyError);
+        assert_eq!(sht31.read().err().unwrap(), SHTError::DataNotReadyError);
+
+        sht31.done();
+    }
+
+    #[test]
+    fn mode_transitions() {
+        let expectations = [
+            // Periodic
+            Transaction::write(ADDR, vec![0x21, 0x30]),
+            // Break
+            Transaction::write(ADDR, vec![0x30, 0x93]),
+            // Periodic
+            Transaction::write(ADDR, vec![0x27, 0x37]),
+            // Break
+            Transaction::write(ADDR, vec![0x30, 0x93]),
+            // Single shot
+            Transaction::write(ADDR, vec![0x24, 0x00]),
+            // Periodic
+            Transaction::write(ADDR, vec![0x21, 0x30]),
+        ];
+        let i2c = Mock::new(&expectations);
+        let mut delay =
+            CheckedDelay::new(&[DelayTransaction::delay_ms(1), DelayTransaction::delay_ms(1)]);
+
+        let sht31 = SHT31::periodic(i2c, Periodic::new())
+            .measure()
+            .unwrap()
+            .into_periodic(Periodic::new().with_mps(MPS::X10), &mut delay)
+            .unwrap()
+            .measure()
+            .unwrap()
+            .into_single_shot(&mut delay)
+            .unwrap()
+            .measure()
+            .unwrap()
+            .into_periodic(Periodic::new())
+            .measure()
+            .unwrap();
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[test]
+    fn periodic_effective_config() {
+        let periodic = Periodic::new().with_mps(MPS::X10);
+        assert_eq!(periodic.effective_mps(), MPS::X10);
+        assert_eq!(periodic.period_ms(), 100);
+
+        let sht31 = SHT31::periodic(Mock::new(&[]), periodic).with_accuracy(Accuracy::Low);
+        assert_eq!(sht31.effective_accuracy(), Some(Accuracy::Low));
+
+        let periodic = periodic.with_art();
+        assert_eq!(periodic.mps(), MPS::X10);
+        assert_eq!(periodic.effective_mps(), MPS::X4);
+        assert_eq!(periodic.period_ms(), 250);
+
+        let sht31 = SHT31::periodic(sht31.destroy(), periodic).with_accuracy(Accuracy::Low);
+        assert_eq!(sht31.effective_accuracy(), None);
+
+        sht31.done();
+    }
+
+    #[rstest]
+    #[case(3f32, FrequencyRounding::Nearest, Ok(MPS::Double))]
+    #[case(20f32, FrequencyRounding::Nearest, Ok(MPS::X10))]
+    #[case(0.1, FrequencyRounding::Nearest, Ok(MPS::Half))]
+    #[case(3f32, FrequencyRounding::Up, Ok(MPS::X4))]
+    #[case(20f32, FrequencyRounding::Up, Err(SHTError::UnsupportedFrequencyError))]
+    #[case(3f32, FrequencyRounding::Down, Ok(MPS::Double))]
+    #[case(0.1, FrequencyRounding::Down, Err(SHTError::UnsupportedFrequencyError))]
+    #[case(0.5, FrequencyRounding::Exact, Ok(MPS::Half))]
+    #[case(
+        3f32,
+        FrequencyRounding::Exact,
+        Err(SHTError::UnsupportedFrequencyError)
+    )]
+    #[case(
+        0f32,
+        FrequencyRounding::Nearest,
+        Err(SHTError::UnsupportedFrequencyError)
+    )]
+    #[case(
+        f32::NAN,
+        FrequencyRounding::Nearest,
+        Err(SHTError::UnsupportedFrequencyError)
+    )]
+    fn mps_from_frequency(
+        #[case] hz: f32,
+        #[case] rounding: FrequencyRounding,
+        #[case] expected: Result<MPS>,
+    ) {
+        assert_eq!(MPS::from_frequency(hz, rounding), expected);
+    }
+
+    #[test]
+    fn periodic_art_toggle() {
+        let expectations = [
+            // Periodic
+            Transaction::write(ADDR, vec![0x21, 0x30]),
+            // Break
+            Transaction::write(ADDR, vec![0x30, 0x93]),
+            // ART
+            Transaction::write(ADDR, vec![0x2B, 0x32]),
+            // Break
+            Transaction::write(ADDR, vec![0x30, 0x93]),
+            // Periodic
+            Transaction::write(ADDR, vec![0x21, 0x30]),
+        ];
+        let i2c = Mock::new(&expectations);
+        let mut delay =
+            CheckedDelay::new(&[DelayTransaction::delay_ms(1), DelayTransaction::delay_ms(1)]);
+
+        let mut sht31 = SHT31::periodic(i2c, Periodic::new()).measure().unwrap();
+        sht31.set_art(true, &mut delay).unwrap();
+        sht31.set_art(false, &mut delay).unwrap();
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[test]
+    fn periodic_art_toggle_failure() {
+        let expectations = [
+            Transaction::write(ADDR, vec![0x21, 0x30]),
+            Transaction::write(ADDR, vec![0x30, 0x93]),
+            Transaction::write(ADDR, vec![0x2B, 0x32]).with_error(ErrorKind::Other),
+            // Measuring again as before
+            Transaction::write(ADDR, vec![0x21, 0x30]),
+        ];
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(1)]);
+
+        let mut sht31 = SHT31::periodic(Mock::new(&expectations), Periodic::new())
+            .measure()
+            .unwrap();
+        assert_eq!(
+            sht31.set_art(true, &mut delay).err().unwrap(),
+            SHTError::WriteI2CError
+        );
+        assert!(!sht31.mode.art());
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[test]
+    fn periodic_stop() {
+        let expectations = [
+            // Periodic
+            Transaction::write(ADDR, vec![0x21, 0x30]),
+            // Break
+            Transaction::write(ADDR, vec![0x30, 0x93]),
+            // Single shot
+            Transaction::write(ADDR, vec![0x24, 0x00]),
+        ];
+        let i2c = Mock::new(&expectations);
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(1)]);
+
+        let sht31 = SHT31::periodic(i2c, Periodic::new())
+            .measure()
+            .unwrap()
+            .stop(&mut delay)
+            .unwrap()
+            .into_single_shot()
+            .measure()
+            .unwrap();
+
+        sht31.done();
+        delay.done();
+    }
+
+    #[test]
+    fn hard_reset() {
+        let expectations = [
+            // Heater On
+            Transaction::write(ADDR, vec![0x30, 0x6D]),
+        ];
+        let mut pin = PinMock::new(&[
+            PinTransaction::set(PinState::Low),
+            PinTransaction::set(PinState::High),
+        ]);
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_us(1),
+            DelayTransaction::delay_us(1500),
+        ]);
+
+        let sht31 = SHT31::periodic(Mock::new(&expectations), Periodic::new())
+            .with_heating()
+            .unwrap()
+            .hard_reset(&mut pin, &mut delay)
+            .unwrap();
+        assert!(!sht31.heater);
+
+        sht31.destroy().done();
+        pin.done();
+        delay.done();
+    }
+
     #[test]
     fn common_interactions() {
         let expectations = [
             // Heater On
-            Transaction::write(DeviceAddr::AD0 as u8, vec![0x30, 0x6D]),
+            Transaction::write(ADDR, vec![0x30, 0x6D]),
             // Heater Off
-            Transaction::write(DeviceAddr::AD0 as u8, vec![0x30, 0x66]),
+            Transaction::write(ADDR, vec![0x30, 0x66]),
             // Break
-            Transaction::write(DeviceAddr::AD0 as u8, vec![0x30, 0x93]),
+            Transaction::write(ADDR, vec![0x30, 0x93]),
             // Soft reset
-            Transaction::write(DeviceAddr::AD0 as u8, vec![0x30, 0xA2]),
-            // Reset
-            Transaction::write(DeviceAddr::AD0 as u8, vec![0x00, 0x06]),
+            Transaction::write(ADDR, vec![0x30, 0xA2]),
+            // General call reset
+            Transaction::write(0x00, vec![0x06]),
             // Reset Status
-            Transaction::write(DeviceAddr::AD0 as u8, vec![0x30, 0x41]),
+            Transaction::write(ADDR, vec![0x30, 0x41]),
         ];
         let i2c = Mock::new(&expectations);
 
@@ -564,7 +2086,9 @@ mod test {
 
         // Resets
         sht31.soft_reset().unwrap();
-        sht31.reset().unwrap();
+        sht31
+            .general_call_reset(GeneralCall::resets_every_device_on_the_bus())
+            .unwrap();
 
         // Status
         sht31.clear_status().unwrap();
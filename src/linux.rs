@@ -0,0 +1,19 @@
+//! Linux convenience for sensors on an i2cdev bus, e.g. a Raspberry Pi's `/dev/i2c-1`
+use crate::{mode::SimpleSingleShot, SHT31};
+use linux_embedded_hal::{Delay, I2CError, I2cdev};
+use std::path::Path;
+
+impl SHT31<SimpleSingleShot<Delay>, I2cdev> {
+    /// Opens the i2cdev bus at `path` and creates a blocking sensor on it
+    /// ```no_run
+    /// use sht31::prelude::*;
+    ///
+    /// let mut sensor = SHT31::open("/dev/i2c-1")?;
+    /// let reading = sensor.read()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn open(path: impl AsRef<Path>) -> core::result::Result<Self, I2CError> {
+        let i2c = I2cdev::new(path)?;
+        Ok(Self::new(i2c, Delay))
+    }
+}
@@ -0,0 +1,185 @@
+//! Low level protocol layer, commands and checksummed responses over a bare I2C bus.
+//! [SHT31](crate::SHT31) and its typed modes are built on top of it, this layer is
+//! for when they're too heavy, e.g. in a bootloader
+use crate::{
+    command::Command,
+    error::{Result, SHTError},
+};
+use embedded_hal::i2c::{Error, ErrorKind, I2c, Operation};
+
+/// A CRC-8 implementation, see [Checksum](crate::checksum::Checksum)
+pub type ChecksumFn = fn([u8; 2]) -> u8;
+
+pub fn write<I2C: I2c>(i2c: &mut I2C, address: u8, bytes: &[u8]) -> Result<()> {
+    i2c.write(address, bytes)
+        .map_err(|_| SHTError::WriteI2CError)
+}
+
+/// A NACK means the sensor has no data ready yet, which is told apart from bus faults
+pub fn read<I2C: I2c>(i2c: &mut I2C, address: u8, buffer: &mut [u8]) -> Result<()> {
+    i2c.read(address, buffer).map_err(|err| match err.kind() {
+        ErrorKind::NoAcknowledge(_) => SHTError::DataNotReadyError,
+        _ => SHTError::ReadI2CError,
+    })
+}
+
+pub fn write_read<I2C: I2c>(
+    i2c: &mut I2C,
+    address: u8,
+    bytes: &[u8],
+    buffer: &mut [u8],
+) -> Result<()> {
+    i2c.write_read(address, bytes, buffer)
+        .map_err(|_| SHTError::WriteReadI2CError)
+}
+
+/// Runs the operations back to back with repeated starts, saving the start and stop
+/// overhead and keeping other masters from interleaving
+pub fn transaction<I2C: I2c>(
+    i2c: &mut I2C,
+    address: u8,
+    operations: &mut [Operation],
+) -> Result<()> {
+    i2c.transaction(address, operations)
+        .map_err(|_| SHTError::WriteReadI2CError)
+}
+
+pub fn send_command<I2C: I2c>(i2c: &mut I2C, address: u8, command: Command) -> Result<()> {
+    write(i2c, address, &command.to_bytes())
+}
+
+/// Checks a word against its trailing checksum, handing back the calculated one on mismatch
+fn verify_word(checksum: ChecksumFn, word: &[u8]) -> core::result::Result<u16, u8> {
+    let calculated = checksum([word[0], word[1]]);
+    if calculated != word[2] {
+        return Err(calculated);
+    }
+    Ok(u16::from_be_bytes([word[0], word[1]]))
+}
+
+/// Verifies a measurement, returning the raw temperature and humidity
+pub fn verify_measurement(checksum: ChecksumFn, buffer: [u8; 6]) -> Result<[u16; 2]> {
+    let temperature = verify_word(checksum, &buffer[0..3]).map_err(|calculated| {
+        SHTError::InvalidTemperatureChecksumError {
+            bytes_start: buffer[0],
+            bytes_end: buffer[1],
+            expected_checksum: buffer[2],
+            calculated_checksum: calculated,
+        }
+    })?;
+
+    let humidity = verify_word(checksum, &buffer[3..6]).map_err(|calculated| {
+        SHTError::InvalidHumidityChecksumError {
+            bytes_start: buffer[3],
+            bytes_end: buffer[4],
+            expected_checksum: buffer[5],
+            calculated_checksum: calculated,
+        }
+    })?;
+
+    Ok([temperature, humidity])
+}
+
+/// Verifies the status register, returning it raw
+pub fn verify_status(checksum: ChecksumFn, buffer: [u8; 3]) -> Result<u16> {
+    verify_word(checksum, &buffer).map_err(|calculated| SHTError::InvalidStatusChecksumError {
+        bytes_start: buffer[0],
+        bytes_end: buffer[1],
+        expected_checksum: buffer[2],
+        calculated_checksum: calculated,
+    })
+}
+
+/// Verifies both words of the serial number and joins them
+pub fn verify_serial_number(checksum: ChecksumFn, buffer: [u8; 6]) -> Result<u32> {
+    let mut words = [0; 2];
+    for (word, bytes) in words.iter_mut().zip(buffer.chunks(3)) {
+        *word = verify_word(checksum, bytes).map_err(|calculated| {
+            SHTError::InvalidSerialNumberChecksumError {
+                bytes_start: bytes[0],
+                bytes_end: bytes[1],
+                expected_checksum: bytes[2],
+                calculated_checksum: calculated,
+            }
+        })?;
+    }
+
+    Ok((words[0] as u32) << 16 | words[1] as u32)
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use super::*;
+    use crate::checksum::{Checksum, SoftwareCrc};
+    use alloc::vec;
+    use embedded_hal::i2c::NoAcknowledgeSource;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    #[test]
+    fn measurement() {
+        let buffer = [98, 153, 188, 98, 32, 139];
+
+        assert_eq!(
+            verify_measurement(SoftwareCrc::checksum, buffer).unwrap(),
+            [0x6299, 0x6220]
+        );
+
+        let corrupt_temperature = [98, 153, 180, 98, 32, 139];
+
+        assert_eq!(
+            verify_measurement(SoftwareCrc::checksum, corrupt_temperature)
+                .err()
+                .unwrap(),
+            SHTError::InvalidTemperatureChecksumError {
+                bytes_start: 98,
+                bytes_end: 153,
+                expected_checksum: 180,
+                calculated_checksum: 188
+            }
+        );
+
+        let corrupt_humidity = [98, 153, 188, 98, 32, 180];
+        assert_eq!(
+            verify_measurement(SoftwareCrc::checksum, corrupt_humidity)
+                .err()
+                .unwrap(),
+            SHTError::InvalidHumidityChecksumError {
+                bytes_start: 98,
+                bytes_end: 32,
+                expected_checksum: 180,
+                calculated_checksum: 139
+            }
+        );
+    }
+
+    #[test]
+    fn bare_bus() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x24, 0x00]),
+            Transaction::read(0x44, vec![0; 6])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            Transaction::write_read(0x44, vec![0x37, 0x80], vec![0, 2, 0xE3, 0, 0, 0x81]),
+        ];
+        let mut i2c = Mock::new(&expectations);
+
+        let command = Command::SingleShot {
+            accuracy: crate::Accuracy::High,
+            clock_stretch: false,
+        };
+        send_command(&mut i2c, 0x44, command).unwrap();
+        assert_eq!(
+            read(&mut i2c, 0x44, &mut [0; 6]),
+            Err(SHTError::DataNotReadyError)
+        );
+
+        let mut buffer = [0; 6];
+        write_read(&mut i2c, 0x44, &[0x37, 0x80], &mut buffer).unwrap();
+        assert_eq!(
+            verify_serial_number(SoftwareCrc::checksum, buffer),
+            Ok(0x0002_0000)
+        );
+
+        i2c.done();
+    }
+}
@@ -0,0 +1,115 @@
+//! Fixed capacity history of readings for loggers without an allocator
+use crate::Reading;
+use heapless::HistoryBuffer;
+
+/// A logged reading
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogEntry {
+    pub reading: Reading,
+    /// Millisecond timestamp supplied when the reading was logged, if any
+    pub timestamp: Option<u32>,
+}
+
+/// Keeps the last `N` readings, once full the oldest reading is overwritten
+#[derive(Clone, Debug)]
+pub struct ReadingLog<const N: usize> {
+    entries: HistoryBuffer<LogEntry, N>,
+}
+
+impl<const N: usize> Default for ReadingLog<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> ReadingLog<N> {
+    pub fn new() -> Self {
+        Self {
+            entries: HistoryBuffer::new(),
+        }
+    }
+
+    /// Log a reading without a timestamp
+    pub fn push(&mut self, reading: Reading) {
+        self.entries.write(LogEntry {
+            reading,
+            timestamp: None,
+        });
+    }
+
+    /// Log a reading taken at the given millisecond timestamp
+    pub fn push_at(&mut self, reading: Reading, timestamp: u32) {
+        self.entries.write(LogEntry {
+            reading,
+            timestamp: Some(timestamp),
+        });
+    }
+
+    /// The most recently logged entry
+    pub fn latest(&self) -> Option<&LogEntry> {
+        self.entries.recent()
+    }
+
+    /// Iterate over the logged entries from oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = &LogEntry> {
+        self.entries.oldest_ordered()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.len() == 0
+    }
+
+    /// Whether the next reading will overwrite the oldest one
+    pub fn is_full(&self) -> bool {
+        self.entries.len() == self.entries.capacity()
+    }
+
+    /// Forget every logged reading
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TemperatureUnit;
+
+    fn reading(temperature: f32) -> Reading {
+        Reading {
+            temperature,
+            humidity: 50f32,
+            unit: TemperatureUnit::Celsius,
+        }
+    }
+
+    #[test]
+    fn overwrites_oldest() {
+        let mut log = ReadingLog::<3>::new();
+        assert!(log.is_empty());
+        assert!(log.latest().is_none());
+
+        log.push(reading(20f32));
+        log.push_at(reading(21f32), 1000);
+        log.push_at(reading(22f32), 2000);
+        assert!(log.is_full());
+        log.push_at(reading(23f32), 3000);
+
+        assert_eq!(log.len(), 3);
+        let mut temperatures = [0f32; 3];
+        for (temperature, entry) in temperatures.iter_mut().zip(log.iter()) {
+            *temperature = entry.reading.temperature;
+        }
+        assert_eq!(temperatures, [21f32, 22f32, 23f32]);
+        assert_eq!(log.iter().next().unwrap().timestamp, Some(1000));
+        assert_eq!(log.latest().unwrap().timestamp, Some(3000));
+
+        log.clear();
+        assert!(log.is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{
+    clock::MaybeClock,
+    command::Command,
+    error::Result,
+    mode::{Sht31Reader, Sht31Sensor},
+    recovery::Rearm,
+    Accuracy, Reading, SHT31,
+};
+use embedded_hal::{
+    delay::DelayNs,
+    i2c::{I2c, Operation},
+};
+
+/// Single shot reading that relies on SCL clock stretching, the sensor holds the clock
+/// low until the measurement is ready so a single read is all that's needed.
+/// Only use this if your bus and HAL tolerate clock stretching
+#[derive(Default, Copy, Clone, Debug)]
+pub struct ClockStretch {}
+
+impl ClockStretch {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// The clock stretching enabled single shot command for the given accuracy
+pub(crate) fn clock_stretch_command(accuracy: Accuracy) -> [u8; 2] {
+    Command::SingleShot {
+        accuracy,
+        clock_stretch: true,
+    }
+    .to_bytes()
+}
+
+impl<I2C, C> Sht31Reader for SHT31<ClockStretch, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Starts a measurement and blocks on the stretched read until it's available,
+    /// both in a single transaction
+    fn read(&mut self) -> Result<Reading> {
+        let mut buffer = [0; 6];
+
+        self.i2c_transaction(&mut [
+            Operation::Write(&clock_stretch_command(self.accuracy)),
+            Operation::Read(&mut buffer),
+        ])?;
+        self.process_data(buffer)
+    }
+}
+
+impl<I2C, C> Sht31Sensor for SHT31<ClockStretch, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// The sensor holds the clock instead, so the delay isn't used
+    fn sample<D: DelayNs>(&mut self, _: &mut D) -> Result<Reading> {
+        self.read()
+    }
+}
+
+impl<I2C, C> Rearm for SHT31<ClockStretch, I2C, C>
+where
+    I2C: I2c,
+{
+    /// Every read starts its own measurement so there's nothing to restore
+    fn rearm(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
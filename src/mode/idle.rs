@@ -0,0 +1,32 @@
+use crate::{
+    mode::{Periodic, SingleShot},
+    SHT31,
+};
+use embedded_hal::i2c::I2c;
+
+/// Typestate marker for a mode that hasn't started measuring, also used as the mode
+/// of a sensor that isn't running any measurement from which any mode can be started
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Idle;
+
+impl<I2C> SHT31<Idle, I2C> {
+    /// A sensor that isn't running any measurement yet
+    pub(crate) fn idle(i2c: I2C) -> Self {
+        Self::with_defaults(Idle, i2c)
+    }
+}
+
+impl<I2C, C> SHT31<Idle, I2C, C>
+where
+    I2C: I2c,
+{
+    /// Switch to single shot
+    pub fn into_single_shot(self) -> SHT31<SingleShot, I2C, C> {
+        self.into_mode(SingleShot::new())
+    }
+
+    /// Switch to periodic, which must be measured to start
+    pub fn into_periodic(self, mode: Periodic) -> SHT31<Periodic, I2C, C> {
+        self.into_mode(mode)
+    }
+}
@@ -1,19 +1,148 @@
-use crate::error::Result;
-use crate::Reading;
+use crate::error::{Result, TransitionResult};
+use crate::health::Health;
+use crate::{Reading, SHT31};
+use embedded_hal::delay::DelayNs;
 
-mod periodic;
-pub use periodic::{Periodic, MPS};
+mod clock_stretch;
+pub use clock_stretch::ClockStretch;
+mod idle;
+pub use idle::Idle;
+pub(crate) mod periodic;
+pub use periodic::{FrequencyRounding, Periodic, MPS};
 mod single_shot;
 pub use single_shot::SingleShot;
 mod simple_single_shot;
-pub use simple_single_shot::SimpleSingleShot;
+pub use simple_single_shot::{Backoff, SimpleSingleShot};
+mod timed_single_shot;
+pub use timed_single_shot::{CountDown, TimedSingleShot};
+mod split;
+pub use split::{FetchRequest, SplitFetch};
 
 pub trait Sht31Reader {
     /// Read the sensor readings
     fn read(&mut self) -> Result<Reading>;
 }
 
-pub trait Sht31Measure {
+impl<R: Sht31Reader + ?Sized> Sht31Reader for &mut R {
+    fn read(&mut self) -> Result<Reading> {
+        (**self).read()
+    }
+}
+
+/// Object safe view of a sensor ready to read, so sensors in different modes and on
+/// different buses can sit together as `&mut dyn DynSht31Reader`
+pub trait DynSht31Reader: Sht31Reader {
+    fn address(&self) -> u8;
+
+    fn health(&self) -> Health;
+}
+
+impl<Mode, I2C, C> DynSht31Reader for SHT31<Mode, I2C, C>
+where
+    SHT31<Mode, I2C, C>: Sht31Reader,
+{
+    fn address(&self) -> u8 {
+        self.address
+    }
+
+    fn health(&self) -> Health {
+        SHT31::health(self)
+    }
+}
+
+/// Any sensor that can produce a reading on demand, measuring first where the mode needs it.
+/// Periodic sensors have to be measuring already since starting them changes their type
+pub trait Sht31Sensor {
+    /// Takes a reading, waiting on `delay` for the measurement if the mode needs to
+    fn sample<D: DelayNs>(&mut self, delay: &mut D) -> Result<Reading>;
+}
+
+pub trait Sht31Measure: Sized {
+    /// The sensor once it has started measuring
+    type Measuring;
+
     /// Initialize reading
-    fn measure(&mut self) -> Result<()>;
+    fn measure(self) -> TransitionResult<Self::Measuring, Self>;
+}
+
+/// Typestate marker for a mode that has started measuring and can be read from
+///
+/// ```compile_fail
+/// # use embedded_hal_mock::eh1::i2c::Mock;
+/// use sht31::prelude::*;
+///
+/// let mut sht = SHT31::single_shot(Mock::new(&[]), SingleShot::new());
+/// // Reading before measuring doesn't compile
+/// sht.read();
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Measuring;
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use crate::{
+        prelude::*,
+        test_utils::{fetch_expectations, single_shot_expectations},
+    };
+    use alloc::vec;
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock, Transaction},
+    };
+
+    #[test]
+    fn registry() {
+        let frame = [98, 153, 188, 98, 32, 139];
+        let mut single_shot = SHT31::single_shot(
+            Mock::new(&single_shot_expectations(
+                0x44,
+                Accuracy::High,
+                false,
+                frame,
+            )),
+            SingleShot::new(),
+        )
+        .measure()
+        .unwrap();
+        let mut simple = SHT31::new(
+            Mock::new(&single_shot_expectations(0x45, Accuracy::High, true, frame)),
+            NoopDelay,
+        )
+        .with_address(DeviceAddr::AD1);
+
+        let mut registry: [&mut dyn DynSht31Reader; 2] = [&mut single_shot, &mut simple];
+        for sensor in registry.iter_mut() {
+            assert_eq!(sensor.read().unwrap().temperature, 72.32318);
+            assert_eq!(sensor.health().total(), 0);
+        }
+        assert_eq!(registry[1].address(), 0x45);
+
+        single_shot.destroy().done();
+        simple.destroy().done();
+    }
+
+    fn sample_twice<S: Sht31Sensor>(sensor: &mut S) -> [f32; 2] {
+        [(); 2].map(|_| sensor.sample(&mut NoopDelay).unwrap().temperature)
+    }
+
+    #[test]
+    fn any_mode() {
+        let frame = [98, 153, 188, 98, 32, 139];
+
+        let [command, read] = single_shot_expectations(0x44, Accuracy::High, false, frame);
+        let expectations = [command.clone(), read.clone(), command, read];
+        let mut single_shot = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+        assert_eq!(sample_twice(&mut single_shot), [72.32318; 2]);
+        single_shot.destroy().done();
+
+        let mut expectations = vec![Transaction::write(0x44, vec![0x21, 0x30])];
+        expectations.extend(fetch_expectations(0x44, frame));
+        expectations.extend(fetch_expectations(0x44, frame));
+        let mut periodic = SHT31::periodic(Mock::new(&expectations), Periodic::new())
+            .measure()
+            .unwrap();
+        assert_eq!(sample_twice(&mut periodic), [72.32318; 2]);
+        periodic.destroy().done();
+    }
 }
@@ -1,20 +1,34 @@
 use crate::{
-    error::Result,
-    mode::{Sht31Measure, Sht31Reader},
+    clock::MaybeClock,
+    command::Command,
+    error::{Result, SHTError, TransitionResult},
+    mode::{Idle, Measuring, Sht31Measure, Sht31Reader, Sht31Sensor, SingleShot},
+    recovery::Rearm,
     Accuracy, Reading, SHT31,
 };
-use embedded_hal::i2c::I2c;
+use core::{marker::PhantomData, ops::ControlFlow};
+use embedded_hal::{delay::DelayNs, i2c::I2c};
 
-/// Periodic reading where reading returns the last available data
-#[derive(Default, Copy, Clone, Debug)]
-pub struct Periodic {
+/// Time the sensor needs after a break command before accepting new commands
+const BREAK_DELAY_MS: u32 = 1;
+/// Time between fetches while waiting for fresh data
+const FETCH_POLL_MS: u32 = 10;
+
+/// Periodic reading where reading returns the last available data,
+/// the state tracks whether the periodic measurements have been started
+#[derive(Copy, Clone, Debug)]
+pub struct Periodic<State = Idle> {
     mps: MPS,
     art: bool,
+    rearm_on_failure: bool,
+    state: PhantomData<State>,
 }
 
 /// Stands for measurements per second
 #[allow(dead_code)]
 #[derive(Default, Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MPS {
     Half = 0x20,
     #[default]
@@ -24,93 +38,412 @@ pub enum MPS {
     X10 = 0x27,
 }
 
+/// How [MPS::from_frequency] handles rates the sensor doesn't support
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrequencyRounding {
+    /// The closest supported rate, ties go to the slower one
+    #[default]
+    Nearest,
+    /// The slowest supported rate at or above the requested one
+    Up,
+    /// The fastest supported rate at or below the requested one
+    Down,
+    /// Only a rate the sensor supports as is
+    Exact,
+}
+
+impl MPS {
+    /// Every rate from slowest to fastest
+    const ALL: [MPS; 5] = [MPS::Half, MPS::Normal, MPS::Double, MPS::X4, MPS::X10];
+
+    /// The supported rate for a frequency in Hz, e.g. from a configuration file.
+    /// Fails when the rounding finds no rate or the frequency isn't a positive number
+    pub fn from_frequency(hz: f32, rounding: FrequencyRounding) -> Result<MPS> {
+        if !(hz > 0f32 && hz.is_finite()) {
+            return Err(SHTError::UnsupportedFrequencyError);
+        }
+
+        let mut rates = MPS::ALL.into_iter();
+        let mps = match rounding {
+            FrequencyRounding::Nearest => rates.min_by(|a, b| {
+                let distance = |mps: &MPS| (mps.per_second() - hz).abs();
+                distance(a).total_cmp(&distance(b))
+            }),
+            FrequencyRounding::Up => rates.find(|mps| mps.per_second() >= hz),
+            FrequencyRounding::Down => rates.rev().find(|mps| mps.per_second() <= hz),
+            FrequencyRounding::Exact => rates.find(|mps| mps.per_second() == hz),
+        };
+
+        mps.ok_or(SHTError::UnsupportedFrequencyError)
+    }
+
+    /// Time between two measurements in milliseconds
+    pub fn period_ms(&self) -> u32 {
+        match self {
+            MPS::Half => 2_000,
+            MPS::Normal => 1_000,
+            MPS::Double => 500,
+            MPS::X4 => 250,
+            MPS::X10 => 100,
+        }
+    }
+}
+
 impl Periodic {
     #[allow(dead_code)]
     pub fn new() -> Self {
         Self {
             mps: MPS::Normal,
             art: false,
+            rearm_on_failure: false,
+            state: PhantomData,
         }
     }
 
     /// Sets the measurements per second for the periodic readings,
-    /// NOTE: The higher the frequency the less accurate the readings will be
+    /// NOTE: The higher the frequency the less accurate the readings will be,
+    /// this is ignored while ART is enabled
     pub fn set_mps(&mut self, mps: MPS) {
         self.mps = mps;
     }
 
     /// Sets the measurements per second for the periodic readings,
-    /// NOTE: The higher the frequency the less accurate the readings will be
+    /// NOTE: The higher the frequency the less accurate the readings will be,
+    /// this is ignored while ART is enabled
     pub fn with_mps(mut self, mps: MPS) -> Self {
         self.set_mps(mps);
         self
     }
 
-    /// Enables accelerated response time, evaluates data at a frequency of 4 Hz
-    pub fn set_art(&mut self) {
-        self.art = true;
+    /// Enables or disables accelerated response time, which evaluates data at a frequency of 4 Hz
+    pub fn set_art(&mut self, art: bool) {
+        self.art = art;
     }
 
     /// Enables accelerated response time, evaluates data at a frequency of 4 Hz
     pub fn with_art(mut self) -> Self {
-        self.set_art();
+        self.set_art(true);
+        self
+    }
+
+    /// Checks the status after every failed fetch and restarts the measurements if the
+    /// sensor was reset, e.g. by a brown-out. Clear the status before measuring, see
+    /// [SHT31::init](crate::SHT31::init), so the power-up reset isn't mistaken for one
+    pub fn set_rearm_on_failure(&mut self, rearm: bool) {
+        self.rearm_on_failure = rearm;
+    }
+
+    /// Checks the status after every failed fetch and restarts the measurements if the
+    /// sensor was reset, e.g. by a brown-out. Clear the status before measuring, see
+    /// [SHT31::init](crate::SHT31::init), so the power-up reset isn't mistaken for one
+    pub fn with_rearm_on_failure(mut self) -> Self {
+        self.set_rearm_on_failure(true);
         self
     }
 }
 
-impl<I2C> Sht31Reader for SHT31<Periodic, I2C>
+impl Default for Periodic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<State> Periodic<State> {
+    /// The configured measurements per second
+    pub fn mps(&self) -> MPS {
+        self.mps
+    }
+
+    /// Whether accelerated response time is enabled
+    pub fn art(&self) -> bool {
+        self.art
+    }
+
+    /// The measurements per second the sensor actually runs at,
+    /// ART overrides the configured MPS with 4 Hz
+    pub fn effective_mps(&self) -> MPS {
+        if self.art {
+            MPS::X4
+        } else {
+            self.mps
+        }
+    }
+
+    /// Time between two measurements in milliseconds at the effective MPS
+    pub fn period_ms(&self) -> u32 {
+        self.effective_mps().period_ms()
+    }
+
+    fn with_state<NewState>(&self) -> Periodic<NewState> {
+        Periodic {
+            mps: self.mps,
+            art: self.art,
+            rearm_on_failure: self.rearm_on_failure,
+            state: PhantomData,
+        }
+    }
+}
+
+/// What to do after a fetch while waiting for fresh data
+pub(crate) enum FreshFetch {
+    Done(Result<Reading>),
+    /// Wait the milliseconds and fetch again
+    Retry(u32),
+}
+
+/// Polling state for fresh periodic data, it only decides and doesn't wait so the
+/// blocking and async waits share the logic and can't drift apart
+pub(crate) struct FreshPoll {
+    waited_ms: u32,
+    timeout_ms: u32,
+}
+
+impl FreshPoll {
+    pub(crate) fn new(timeout_ms: u32) -> Self {
+        Self {
+            waited_ms: 0,
+            timeout_ms,
+        }
+    }
+}
+
+/// The command that starts periodic measurements for the given configuration
+pub(crate) fn periodic_command<State>(mode: &Periodic<State>, accuracy: Accuracy) -> [u8; 2] {
+    let command = if mode.art {
+        Command::Art
+    } else {
+        Command::Periodic {
+            mps: mode.mps,
+            accuracy,
+        }
+    };
+
+    command.to_bytes()
+}
+
+impl<I2C, State, C> SHT31<Periodic<State>, I2C, C> {
+    /// Time between two measurements at the effective MPS in µs
+    pub(crate) fn sample_period_us(&self) -> u32 {
+        self.mode.period_ms() * 1_000
+    }
+}
+
+impl<I2C, State, C> SHT31<Periodic<State>, I2C, C>
 where
     I2C: I2c,
 {
-    fn read(&mut self) -> Result<Reading> {
+    /// The accuracy the periodic measurements actually run at,
+    /// ART has a fixed repeatability so [None] is returned while it's enabled
+    pub fn effective_accuracy(&self) -> Option<Accuracy> {
+        if self.mode.art {
+            None
+        } else {
+            Some(self.accuracy)
+        }
+    }
+}
+
+impl<I2C, C> SHT31<Periodic, I2C, C>
+where
+    I2C: I2c,
+{
+    /// Switch to single shot, no break is needed since periodic measurements haven't started
+    pub fn into_single_shot(self) -> SHT31<SingleShot, I2C, C> {
+        self.into_mode(SingleShot::new())
+    }
+}
+
+impl<I2C, C> SHT31<Periodic<Measuring>, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Sends the break command and waits until the sensor accepts new commands
+    fn stop_measuring<D: DelayNs>(&mut self, delay: &mut D) -> Result<()> {
+        self.break_command()?;
+        delay.delay_ms(BREAK_DELAY_MS);
+        Ok(())
+    }
+
+    /// Enables or disables accelerated response time, the running measurements are
+    /// stopped and restarted with the new configuration. The configuration only changes
+    /// once the sensor took it, otherwise the previous measurements are restarted
+    pub fn set_art<D: DelayNs>(&mut self, art: bool, delay: &mut D) -> Result<()> {
+        let mut mode = self.mode;
+        mode.art = art;
+        let command = periodic_command(&mode, self.accuracy);
+
+        self.stop_measuring(delay)?;
+        if let Err(err) = self.i2c_write(&command) {
+            // Don't leave the sensor idle while it's typed as measuring
+            let _ = self.rearm();
+            return Err(err);
+        }
+        self.mode.art = art;
+        Ok(())
+    }
+
+    /// Restarts the periodic measurements if the status reports the sensor was reset since
+    /// the status was last cleared, which stops them. Returns whether it was re-armed
+    pub fn rearm_if_reset(&mut self) -> Result<bool> {
+        if !self.status()?.system_reset {
+            return Ok(false);
+        }
+        self.clear_status()?;
+        self.rearm()?;
+        Ok(true)
+    }
+
+    fn fetch(&mut self) -> Result<Reading> {
         let mut buffer = [0; 6];
 
-        self.i2c_write_read(&[0xE0, 0x00], &mut buffer)?;
-        self.process_data(buffer)
+        // Sent apart from the read so a missing sensor, which NACKs the command, is told
+        // apart from one without new data, which only NACKs the read
+        self.send_command(Command::FetchData)?;
+        self.i2c_read(&mut buffer)?;
+        let mps = self.mode.effective_mps();
+        self.process_data_at(buffer, Some(mps))
+    }
+
+    /// Fetches once for [FreshPoll], stale data is retried until the timeout runs out
+    pub(crate) fn poll_fresh(&mut self, poll: &mut FreshPoll) -> FreshFetch {
+        match self.read() {
+            Err(SHTError::DataNotReadyError) if poll.waited_ms < poll.timeout_ms => {
+                let ms = FETCH_POLL_MS.min(poll.timeout_ms - poll.waited_ms);
+                poll.waited_ms += ms;
+                FreshFetch::Retry(ms)
+            }
+            Err(SHTError::DataNotReadyError) => {
+                FreshFetch::Done(self.track(Err(SHTError::FetchTimeoutError)))
+            }
+            result => FreshFetch::Done(result),
+        }
+    }
+
+    /// Fetches the next fresh measurement, polling until one arrives or `timeout_ms` elapses
+    /// in which case [SHTError::FetchTimeoutError] is returned
+    pub fn fetch_latest_or_wait<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u32,
+    ) -> Result<Reading> {
+        let mut poll = FreshPoll::new(timeout_ms);
+        loop {
+            match self.poll_fresh(&mut poll) {
+                FreshFetch::Done(result) => return result,
+                FreshFetch::Retry(ms) => delay.delay_ms(ms),
+            }
+        }
+    }
+
+    /// Blocking fetch loop at the sensor's rate, `on_sample` gets every measurement and
+    /// decides whether to carry on. The loop sleeps three quarters of a period and polls
+    /// for the rest, so it stays locked onto the sensor as long as `on_sample` returns
+    /// within a quarter period, otherwise samples are skipped
+    pub fn run<D: DelayNs, B>(
+        &mut self,
+        delay: &mut D,
+        mut on_sample: impl FnMut(Result<Reading>) -> ControlFlow<B>,
+    ) -> B {
+        let period_us = self.sample_period_us();
+        let margin_us = period_us / 4;
+
+        loop {
+            delay.delay_us(period_us - margin_us);
+            let reading = self.fetch_latest_or_wait(delay, 2 * margin_us / 1_000);
+            if let ControlFlow::Break(value) = on_sample(reading) {
+                return value;
+            }
+        }
+    }
+
+    /// Stops the periodic measurements, leaving the sensor idle
+    pub fn stop<D: DelayNs>(
+        mut self,
+        delay: &mut D,
+    ) -> TransitionResult<SHT31<Idle, I2C, C>, Self> {
+        let result = self.stop_measuring(delay);
+        self.transition(result, Idle)
+    }
+
+    /// Stops the periodic measurements and switches to single shot
+    pub fn into_single_shot<D: DelayNs>(
+        mut self,
+        delay: &mut D,
+    ) -> TransitionResult<SHT31<SingleShot, I2C, C>, Self> {
+        let result = self.stop_measuring(delay);
+        self.transition(result, SingleShot::new())
+    }
+
+    /// Stops the periodic measurements and switches to a new periodic configuration,
+    /// which must be measured again to start
+    pub fn into_periodic<D: DelayNs>(
+        mut self,
+        mode: Periodic,
+        delay: &mut D,
+    ) -> TransitionResult<SHT31<Periodic, I2C, C>, Self> {
+        let result = self.stop_measuring(delay);
+        self.transition(result, mode)
     }
 }
 
-impl<I2C> Sht31Measure for SHT31<Periodic, I2C>
+impl<I2C, C> Sht31Reader for SHT31<Periodic<Measuring>, I2C, C>
 where
     I2C: I2c,
+    C: MaybeClock,
 {
+    /// Fetch the latest measurement, the sensor NACKs the read when there's no new data
+    /// since the last fetch which is returned as [SHTError::DataNotReadyError].
+    /// See [Periodic::set_rearm_on_failure] for recovering from sensor resets
+    fn read(&mut self) -> Result<Reading> {
+        let result = self.fetch();
+        if result.is_err() && self.mode.rearm_on_failure {
+            self.rearm_if_reset()?;
+        }
+        result
+    }
+}
+
+impl<I2C, C> Sht31Sensor for SHT31<Periodic<Measuring>, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Waits for the next fresh measurement for up to two sample periods,
+    /// see [SHT31::fetch_latest_or_wait]
+    fn sample<D: DelayNs>(&mut self, delay: &mut D) -> Result<Reading> {
+        let timeout_ms = 2 * self.sample_period_us() / 1_000;
+        self.fetch_latest_or_wait(delay, timeout_ms)
+    }
+}
+
+impl<I2C, C> Rearm for SHT31<Periodic<Measuring>, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Restarts the periodic measurements with the current configuration
+    fn rearm(&mut self) -> Result<()> {
+        let command = periodic_command(&self.mode, self.accuracy);
+        self.i2c_write(&command)
+    }
+}
+
+impl<I2C, C> Sht31Measure for SHT31<Periodic, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    type Measuring = SHT31<Periodic<Measuring>, I2C, C>;
+
     /// Initialized the periodic measuring mode,
     /// a break command must be run in order to change
     /// the measuring style
-    fn measure(&mut self) -> Result<()> {
-        let (msb, lsb) = if self.mode.art {
-            (0x2B, 0x32)
-        } else {
-            let lsb = match self.mode.mps {
-                MPS::Half => match self.accuracy {
-                    Accuracy::High => 0x32,
-                    Accuracy::Medium => 0x24,
-                    Accuracy::Low => 0x2F,
-                },
-                MPS::Normal => match self.accuracy {
-                    Accuracy::High => 0x30,
-                    Accuracy::Medium => 0x26,
-                    Accuracy::Low => 0x2D,
-                },
-                MPS::Double => match self.accuracy {
-                    Accuracy::High => 0x36,
-                    Accuracy::Medium => 0x20,
-                    Accuracy::Low => 0x2B,
-                },
-                MPS::X4 => match self.accuracy {
-                    Accuracy::High => 0x34,
-                    Accuracy::Medium => 0x22,
-                    Accuracy::Low => 0x29,
-                },
-                MPS::X10 => match self.accuracy {
-                    Accuracy::High => 0x37,
-                    Accuracy::Medium => 0x21,
-                    Accuracy::Low => 0x2A,
-                },
-            };
-            (self.mode.mps as u8, lsb)
-        };
-
-        self.i2c_write(&[msb, lsb])
+    fn measure(mut self) -> TransitionResult<Self::Measuring, Self> {
+        let result = self.i2c_write(&periodic_command(&self.mode, self.accuracy));
+        let mode = self.mode.with_state();
+        self.transition(result, mode)
     }
 }
@@ -1,18 +1,49 @@
 use crate::{
-    error::{Result, SHTError::PlaceholderError},
-    mode::{single_shot::single_shot_read, Sht31Reader},
-    Accuracy, Reading, SHT31,
+    clock::MaybeClock,
+    error::{Result, SHTError},
+    mode::{
+        clock_stretch::clock_stretch_command, single_shot::single_shot_read, Sht31Reader,
+        Sht31Sensor,
+    },
+    recovery::Rearm,
+    Reading, SHT31,
 };
 use embedded_hal::{delay::DelayNs, i2c::I2c};
 
-/// A simple reading that blocks until the measurement is obtained
+/// A simple reading that blocks until the measurement is obtained, `Y` is the
+/// optional hook waiting between tries instead of the delay
 #[derive(Copy, Clone, Debug)]
-pub struct SimpleSingleShot<D: DelayNs> {
+pub struct SimpleSingleShot<D: DelayNs, Y = fn(u32)> {
     max_retries: u8,
-    ms_delay: u32,
+    backoff: Backoff,
+    yield_hook: Option<Y>,
     pub(crate) delay: D,
 }
 
+/// How long to wait between read attempts
+#[derive(Copy, Clone, Debug)]
+pub enum Backoff {
+    /// Always wait the same milliseconds
+    Fixed(u32),
+    /// Start at `initial_ms` and double after every retry up to `max_ms`
+    Exponential { initial_ms: u32, max_ms: u32 },
+    /// Milliseconds to wait given the retry number, starting at 0
+    Custom(fn(u8) -> u32),
+}
+
+impl Backoff {
+    /// Milliseconds to wait before the given retry, starting at 0
+    pub fn delay_ms(&self, retry: u8) -> u32 {
+        match *self {
+            Backoff::Fixed(ms) => ms,
+            Backoff::Exponential { initial_ms, max_ms } => initial_ms
+                .saturating_mul(2u32.saturating_pow(retry as u32))
+                .min(max_ms),
+            Backoff::Custom(f) => f(retry),
+        }
+    }
+}
+
 impl<D> SimpleSingleShot<D>
 where
     D: DelayNs,
@@ -21,10 +52,18 @@ where
     pub fn new(delay: D) -> Self {
         Self {
             max_retries: 8,
-            ms_delay: 100,
+            backoff: Backoff::Fixed(100),
+            yield_hook: None,
             delay,
         }
     }
+}
+
+impl<D, Y> SimpleSingleShot<D, Y>
+where
+    D: DelayNs,
+    Y: FnMut(u32),
+{
     /// Sets the max number of retries to read a sensor before giving up
     pub fn set_max_retries(&mut self, max_retries: u8) {
         self.max_retries = max_retries
@@ -36,43 +75,149 @@ where
     }
     /// Sets the millisecond delay between each try
     pub fn set_delay(&mut self, ms_delay: u32) {
-        self.ms_delay = ms_delay
+        self.set_backoff(Backoff::Fixed(ms_delay))
     }
     /// Sets the millisecond delay between each try
     pub fn with_delay(mut self, ms_delay: u32) -> Self {
         self.set_delay(ms_delay);
         self
     }
+    /// Sets how long to wait between each try
+    pub fn set_backoff(&mut self, backoff: Backoff) {
+        self.backoff = backoff
+    }
+    /// Sets how long to wait between each try
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.set_backoff(backoff);
+        self
+    }
+    /// Sets a hook called with the backoff's milliseconds between tries instead of the
+    /// blocking delay, e.g. to sleep or yield through an RTOS or to feed a watchdog
+    pub fn set_yield_hook(&mut self, hook: Option<Y>) {
+        self.yield_hook = hook
+    }
+    /// Sets a hook called with the backoff's milliseconds between tries instead of the
+    /// blocking delay, e.g. to sleep or yield through an RTOS or to feed a watchdog.
+    /// The hook can capture state such as a watchdog handle
+    pub fn with_yield_hook<F: FnMut(u32)>(self, hook: F) -> SimpleSingleShot<D, F> {
+        SimpleSingleShot {
+            max_retries: self.max_retries,
+            backoff: self.backoff,
+            yield_hook: Some(hook),
+            delay: self.delay,
+        }
+    }
 }
 
-impl<I2C, D> Sht31Reader for SHT31<SimpleSingleShot<D>, I2C>
+impl<I2C, D, Y, C> Sht31Reader for SHT31<SimpleSingleShot<D, Y>, I2C, C>
 where
     I2C: I2c,
     D: DelayNs,
+    Y: FnMut(u32),
+    C: MaybeClock,
 {
-    /// It will initiate a read and wont stop until its either exhausted its retries or a reading is found
+    /// It will initiate a read and wont stop until its either exhausted its retries or a reading is found.
+    /// Only a sensor that has no data ready yet is retried, running out of retries is a
+    /// [SHTError::ReadingTimeoutError] while any other error is returned right away
     fn read(&mut self) -> Result<Reading> {
         // Commence reading
-        let lsb = match self.accuracy {
-            Accuracy::High => 0x06,
-            Accuracy::Medium => 0x0D,
-            Accuracy::Low => 0x10,
-        };
+        self.i2c_write(&clock_stretch_command(self.accuracy))?;
 
-        self.i2c_write(&[0x2C, lsb])?;
+        for attempt in 0..self.mode.max_retries {
+            if attempt > 0 {
+                let ms = self.mode.backoff.delay_ms(attempt - 1);
+                match &mut self.mode.yield_hook {
+                    Some(hook) => hook(ms),
+                    None => self.mode.delay.delay_ms(ms),
+                }
+            }
 
-        // TODO: figure out clock stretching
-        let mut read_attempt = Err(PlaceholderError);
+            match single_shot_read(self) {
+                Err(SHTError::DataNotReadyError) => continue,
+                result => return result,
+            }
+        }
 
-        for _ in 0..self.mode.max_retries {
-            read_attempt = single_shot_read(self);
+        let attempts = self.mode.max_retries;
+        self.track(Err(SHTError::ReadingTimeoutError { attempts }))
+    }
+}
 
-            if read_attempt.is_err() {
-                self.mode.delay.delay_ms(self.mode.ms_delay);
-            } else {
-                return read_attempt;
-            }
+impl<I2C, D, Y, C> Sht31Sensor for SHT31<SimpleSingleShot<D, Y>, I2C, C>
+where
+    I2C: I2c,
+    D: DelayNs,
+    Y: FnMut(u32),
+    C: MaybeClock,
+{
+    /// The mode's own delay is used for the retries
+    fn sample<E: DelayNs>(&mut self, _: &mut E) -> Result<Reading> {
+        self.read()
+    }
+}
+
+impl<I2C, D, Y, C> Rearm for SHT31<SimpleSingleShot<D, Y>, I2C, C>
+where
+    I2C: I2c,
+    D: DelayNs,
+    Y: FnMut(u32),
+{
+    /// Every read starts its own measurement so there's nothing to restore
+    fn rearm(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use super::*;
+    use alloc::vec;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    /// Fails the test if the blocking delay is used
+    struct NoDelay;
+
+    impl DelayNs for NoDelay {
+        fn delay_ns(&mut self, _: u32) {
+            panic!("blocking delay used");
         }
-        read_attempt
+    }
+
+    #[test]
+    fn backoff() {
+        assert_eq!(Backoff::Fixed(100).delay_ms(5), 100);
+
+        let exponential = Backoff::Exponential {
+            initial_ms: 1,
+            max_ms: 10,
+        };
+        let delays = [0, 1, 2, 3, 4, 200].map(|retry| exponential.delay_ms(retry));
+        assert_eq!(delays, [1, 2, 4, 8, 10, 10]);
+
+        let custom = Backoff::Custom(|retry| if retry < 3 { 1 } else { 50 });
+        assert_eq!(custom.delay_ms(2), 1);
+        assert_eq!(custom.delay_ms(3), 50);
+    }
+
+    #[test]
+    fn yield_hook() {
+        let not_ready = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+        let expectations = [
+            Transaction::write(0x44, vec![0x2C, 0x06]),
+            Transaction::read(0x44, vec![0; 6]).with_error(not_ready),
+            Transaction::read(0x44, vec![0; 6]).with_error(not_ready),
+            Transaction::read(0x44, vec![98, 153, 188, 98, 32, 139]),
+        ];
+        let mut waited_ms = 0;
+        let mode = SimpleSingleShot::new(NoDelay)
+            .with_delay(5)
+            .with_yield_hook(|ms| waited_ms += ms);
+        let mut sensor = SHT31::simple_single_shot(Mock::new(&expectations), mode);
+
+        assert_eq!(sensor.read().unwrap().temperature, 72.32318);
+        sensor.destroy().done();
+        assert_eq!(waited_ms, 10);
     }
 }
@@ -1,52 +1,152 @@
 use crate::{
-    error::Result,
-    mode::{Sht31Measure, Sht31Reader},
+    clock::MaybeClock,
+    command::Command,
+    error::{Result, SHTError, TransitionResult},
+    mode::{Idle, Measuring, Periodic, Sht31Measure, Sht31Reader, Sht31Sensor},
+    stats::{Averaged, Stats},
     Accuracy, Reading, SHT31,
 };
-use embedded_hal::i2c::I2c;
+use core::{marker::PhantomData, num::NonZeroU16};
+use embedded_hal::{delay::DelayNs, i2c::I2c};
 
-/// Complex read that may require multiple attempts to read output until its ready
-#[derive(Default, Copy, Clone, Debug)]
-pub struct SingleShot {}
+/// Complex read that may require multiple attempts to read output until its ready,
+/// the state tracks whether a measurement has been started
+#[derive(Copy, Clone, Debug)]
+pub struct SingleShot<State = Idle> {
+    state: PhantomData<State>,
+}
 
 impl SingleShot {
     #[allow(dead_code)]
     pub fn new() -> Self {
-        Self {}
+        Self { state: PhantomData }
+    }
+}
+
+impl Default for SingleShot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<State> SingleShot<State> {
+    fn with_state<NewState>(&self) -> SingleShot<NewState> {
+        SingleShot { state: PhantomData }
+    }
+}
+
+/// The single shot command without clock stretching for the given accuracy
+pub(crate) fn single_shot_command(accuracy: Accuracy) -> [u8; 2] {
+    Command::SingleShot {
+        accuracy,
+        clock_stretch: false,
     }
+    .to_bytes()
 }
 
-pub(crate) fn single_shot_read<Mode, I2C: I2c>(sensor: &mut SHT31<Mode, I2C>) -> Result<Reading> {
-    // TODO: If error is a NACK then return another unique error to identify
+pub(crate) fn single_shot_read<Mode, I2C: I2c, C: MaybeClock>(
+    sensor: &mut SHT31<Mode, I2C, C>,
+) -> Result<Reading> {
     let mut buffer = [0; 6];
 
     sensor.i2c_read(&mut buffer)?;
     sensor.process_data(buffer)
 }
 
-impl<I2C> Sht31Reader for SHT31<SingleShot, I2C>
+impl<I2C, State, C> SHT31<SingleShot<State>, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Switch to periodic, single shot measurements don't need to be broken out of
+    pub fn into_periodic(self, mode: Periodic) -> SHT31<Periodic, I2C, C> {
+        self.into_mode(mode)
+    }
+
+    /// Starts a measurement, waits the accuracy's maximum conversion time and reads it
+    pub fn measure_and_wait<D: DelayNs>(&mut self, delay: &mut D) -> Result<Reading> {
+        self.i2c_write(&single_shot_command(self.accuracy))?;
+        delay.delay_us(self.accuracy.max_duration_us());
+        single_shot_read(self)
+    }
+
+    /// Takes `samples` back to back measurements and averages them, e.g. for a low noise
+    /// reference while calibrating. The count is a [NonZeroU16] since there's nothing to
+    /// average without a measurement. Any failed measurement fails it
+    pub fn read_averaged<D: DelayNs>(
+        &mut self,
+        samples: NonZeroU16,
+        delay: &mut D,
+    ) -> Result<Averaged> {
+        let mut stats = Stats::new();
+        for _ in 0..samples.get() {
+            stats.push(&self.measure_and_wait(delay)?);
+        }
+
+        // At least one reading was fed
+        Ok(Averaged::from_stats(&stats, self.unit).unwrap())
+    }
+
+    /// Battery friendly [measure_and_wait](Self::measure_and_wait), always measures with
+    /// [Accuracy::Low] regardless of the configured accuracy and only touches the bus to
+    /// send the command and fetch the result, the sensor drops back to idle right after.
+    /// At one reading per second this averages around 2.9 µA, with the sensor drawing
+    /// about 0.2 µA between readings, see
+    /// [estimated_current_ua](SHT31::estimated_current_ua)
+    pub fn read_low_power<D: DelayNs>(&mut self, delay: &mut D) -> Result<Reading> {
+        self.i2c_write(&single_shot_command(Accuracy::Low))?;
+        delay.delay_us(Accuracy::Low.max_duration_us());
+        single_shot_read(self)
+    }
+}
+
+impl<I2C, C> SHT31<SingleShot<Measuring>, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Non blocking read, returns [nb::Error::WouldBlock] while the measurement is still running
+    pub fn read_nb(&mut self) -> nb::Result<Reading, SHTError> {
+        let mut buffer = [0; 6];
+
+        self.i2c_read_nb(&mut buffer)?;
+        Ok(self.process_data(buffer)?)
+    }
+}
+
+impl<I2C, C> Sht31Reader for SHT31<SingleShot<Measuring>, I2C, C>
 where
     I2C: I2c,
+    C: MaybeClock,
 {
-    /// Try reading, if the reading is not available yet then it will return an error
+    /// Try reading, if the reading is not available yet then it will return [SHTError::DataNotReadyError]
     fn read(&mut self) -> Result<Reading> {
         single_shot_read(self)
     }
 }
 
-impl<I2C> Sht31Measure for SHT31<SingleShot, I2C>
+impl<I2C, State, C> Sht31Sensor for SHT31<SingleShot<State>, I2C, C>
 where
     I2C: I2c,
+    C: MaybeClock,
 {
-    /// Commence measuring
-    #[allow(dead_code)]
-    fn measure(&mut self) -> Result<()> {
-        let lsb = match self.accuracy {
-            Accuracy::High => 0x00,
-            Accuracy::Medium => 0x0B,
-            Accuracy::Low => 0x16,
-        };
-
-        self.i2c_write(&[0x24, lsb])
+    /// Starts a new measurement and waits for it, see [SHT31::measure_and_wait]
+    fn sample<D: DelayNs>(&mut self, delay: &mut D) -> Result<Reading> {
+        self.measure_and_wait(delay)
+    }
+}
+
+impl<I2C, State, C> Sht31Measure for SHT31<SingleShot<State>, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    type Measuring = SHT31<SingleShot<Measuring>, I2C, C>;
+
+    /// Commence measuring, can also be called after reading to start the next measurement
+    fn measure(mut self) -> TransitionResult<Self::Measuring, Self> {
+        let result = self.i2c_write(&single_shot_command(self.accuracy));
+        let mode = self.mode.with_state();
+        self.transition(result, mode)
     }
 }
@@ -0,0 +1,143 @@
+use crate::{
+    clock::MaybeClock,
+    command::Command,
+    error::Result,
+    mode::{
+        clock_stretch::clock_stretch_command, periodic::periodic_command,
+        single_shot::single_shot_command, ClockStretch, Periodic, SingleShot,
+    },
+    Reading, SHT31,
+};
+
+/// Length of a measurement, two words with their checksums
+const FETCH_LEN: usize = 6;
+
+/// The bus operations behind a fetch, for I2C engines outside embedded-hal
+/// such as DMA driven ones
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FetchRequest {
+    pub address: u8,
+    /// Command to write first
+    pub command: [u8; 2],
+    /// Time to wait between writing the command and reading
+    pub wait_us: u32,
+    /// Amount of bytes to read back and pass to [SplitFetch::finish_fetch]
+    pub read_len: usize,
+}
+
+/// Two-phase fetch, the caller runs the [FetchRequest] on its own bus and hands the
+/// bytes back for checksum verification and conversion
+pub trait SplitFetch {
+    fn start_fetch(&self) -> FetchRequest;
+
+    fn finish_fetch(&mut self, buffer: [u8; FETCH_LEN]) -> Result<Reading>;
+}
+
+impl<State, I2C, C> SHT31<Periodic<State>, I2C, C> {
+    /// The command that starts the periodic measurements, which has to be written
+    /// once before fetching
+    pub fn start_command(&self) -> [u8; 2] {
+        periodic_command(&self.mode, self.accuracy)
+    }
+}
+
+impl<State, I2C, C> SplitFetch for SHT31<Periodic<State>, I2C, C>
+where
+    C: MaybeClock,
+{
+    fn start_fetch(&self) -> FetchRequest {
+        FetchRequest {
+            address: self.address,
+            command: Command::FetchData.to_bytes(),
+            wait_us: 0,
+            read_len: FETCH_LEN,
+        }
+    }
+
+    fn finish_fetch(&mut self, buffer: [u8; FETCH_LEN]) -> Result<Reading> {
+        let mps = self.mode.effective_mps();
+        self.process_data_at(buffer, Some(mps))
+    }
+}
+
+impl<State, I2C, C> SplitFetch for SHT31<SingleShot<State>, I2C, C>
+where
+    C: MaybeClock,
+{
+    /// Measures and waits the accuracy's maximum conversion time
+    fn start_fetch(&self) -> FetchRequest {
+        FetchRequest {
+            address: self.address,
+            command: single_shot_command(self.accuracy),
+            wait_us: self.accuracy.max_duration_us(),
+            read_len: FETCH_LEN,
+        }
+    }
+
+    fn finish_fetch(&mut self, buffer: [u8; FETCH_LEN]) -> Result<Reading> {
+        self.process_data(buffer)
+    }
+}
+
+impl<I2C, C> SplitFetch for SHT31<ClockStretch, I2C, C>
+where
+    C: MaybeClock,
+{
+    /// Measures and reads right away, the engine has to tolerate clock stretching
+    fn start_fetch(&self) -> FetchRequest {
+        FetchRequest {
+            address: self.address,
+            command: clock_stretch_command(self.accuracy),
+            wait_us: 0,
+            read_len: FETCH_LEN,
+        }
+    }
+
+    fn finish_fetch(&mut self, buffer: [u8; FETCH_LEN]) -> Result<Reading> {
+        self.process_data(buffer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{mode::FetchRequest, prelude::*, SHTError};
+
+    /// Stands in for a bus that isn't driven through embedded-hal
+    struct DmaBus;
+
+    #[test]
+    fn periodic() {
+        let mut sensor = SHT31::periodic(DmaBus, Periodic::new().with_mps(MPS::X10));
+        assert_eq!(sensor.start_command(), [0x27, 0x37]);
+
+        assert_eq!(
+            sensor.start_fetch(),
+            FetchRequest {
+                address: 0x44,
+                command: [0xE0, 0x00],
+                wait_us: 0,
+                read_len: 6,
+            }
+        );
+        let reading = sensor.finish_fetch([98, 153, 188, 98, 32, 139]).unwrap();
+        assert_eq!(reading.temperature, 72.32318);
+    }
+
+    #[test]
+    fn single_shot() {
+        let mut sensor = SHT31::single_shot(DmaBus, SingleShot::new())
+            .with_accuracy(Accuracy::Low)
+            .with_address(DeviceAddr::AD1);
+
+        let request = sensor.start_fetch();
+        assert_eq!(request.address, 0x45);
+        assert_eq!(request.command, [0x24, 0x16]);
+        assert_eq!(request.wait_us, 4_500);
+
+        assert!(matches!(
+            sensor.finish_fetch([98, 153, 0, 98, 32, 139]),
+            Err(SHTError::InvalidTemperatureChecksumError { .. })
+        ));
+        assert_eq!(sensor.health().crc_errors, 1);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{
+    clock::MaybeClock,
+    error::{Result, SHTError},
+    mode::{single_shot::single_shot_command, Sht31Reader, Sht31Sensor},
+    Reading, SHT31,
+};
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+
+/// A hardware timer that's checked for expiry instead of blocked on,
+/// embedded-hal 1.0 dropped its own so HAL timers need a small adapter
+pub trait CountDown {
+    /// Starts counting down from the given µs, restarting if already running
+    fn start(&mut self, us: u32);
+
+    fn is_expired(&mut self) -> bool;
+}
+
+/// Single shot reading timed by a [CountDown], polling lets superloop firmware
+/// do other work while the measurement runs
+#[derive(Copy, Clone, Debug)]
+pub struct TimedSingleShot<T: CountDown> {
+    timer: T,
+    measuring: bool,
+}
+
+impl<T> TimedSingleShot<T>
+where
+    T: CountDown,
+{
+    pub fn new(timer: T) -> Self {
+        Self {
+            timer,
+            measuring: false,
+        }
+    }
+}
+
+impl<I2C, T, C> SHT31<TimedSingleShot<T>, I2C, C>
+where
+    I2C: I2c,
+    T: CountDown,
+    C: MaybeClock,
+{
+    /// Starts a measurement and times the accuracy's maximum conversion time,
+    /// the measurement is only read once the timer has expired
+    fn start_measurement(&mut self) -> Result<()> {
+        self.i2c_write(&single_shot_command(self.accuracy))?;
+        self.mode.timer.start(self.accuracy.max_duration_us());
+        self.mode.measuring = true;
+        Ok(())
+    }
+
+    /// Non blocking read, the first poll starts a measurement and [nb::Error::WouldBlock]
+    /// is returned until it's done. The poll after a reading starts the next measurement
+    pub fn poll(&mut self) -> nb::Result<Reading, SHTError> {
+        if !self.mode.measuring {
+            self.start_measurement()?;
+            return Err(nb::Error::WouldBlock);
+        }
+        if !self.mode.timer.is_expired() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let mut buffer = [0; 6];
+        match self.i2c_read_nb(&mut buffer) {
+            Err(nb::Error::WouldBlock) => return Err(nb::Error::WouldBlock),
+            result => {
+                self.mode.measuring = false;
+                result?;
+            }
+        }
+        Ok(self.process_data(buffer)?)
+    }
+
+    /// Whether a measurement is running
+    pub fn is_measuring(&self) -> bool {
+        self.mode.measuring
+    }
+}
+
+impl<I2C, T, C> Sht31Reader for SHT31<TimedSingleShot<T>, I2C, C>
+where
+    I2C: I2c,
+    T: CountDown,
+    C: MaybeClock,
+{
+    /// Polls until the running measurement, or a new one if none is running, is read
+    fn read(&mut self) -> Result<Reading> {
+        nb::block!(self.poll())
+    }
+}
+
+impl<I2C, T, C> Sht31Sensor for SHT31<TimedSingleShot<T>, I2C, C>
+where
+    I2C: I2c,
+    T: CountDown,
+    C: MaybeClock,
+{
+    /// The mode's timer is used instead of the delay
+    fn sample<D: DelayNs>(&mut self, _: &mut D) -> Result<Reading> {
+        self.read()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use super::*;
+    use crate::prelude::*;
+    use alloc::vec;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    /// Expires after being checked the given amount of times
+    struct Polls {
+        remaining: u8,
+        started_us: u32,
+    }
+
+    impl CountDown for Polls {
+        fn start(&mut self, us: u32) {
+            self.remaining = 2;
+            self.started_us = us;
+        }
+
+        fn is_expired(&mut self) -> bool {
+            self.remaining = self.remaining.saturating_sub(1);
+            self.remaining == 0
+        }
+    }
+
+    #[test]
+    fn poll() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x24, 0x0B]),
+            Transaction::read(0x44, vec![0; 6])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            Transaction::read(0x44, vec![98, 153, 188, 98, 32, 139]),
+        ];
+        let timer = Polls {
+            remaining: 0,
+            started_us: 0,
+        };
+        let mut sensor =
+            SHT31::timed_single_shot(Mock::new(&expectations), TimedSingleShot::new(timer))
+                .with_accuracy(Accuracy::Medium);
+
+        // Starts measuring
+        assert_eq!(sensor.poll().unwrap_err(), nb::Error::WouldBlock);
+        assert!(sensor.is_measuring());
+        assert_eq!(sensor.mode.timer.started_us, 6_500);
+        // Timer still running, the bus isn't touched
+        assert_eq!(sensor.poll().unwrap_err(), nb::Error::WouldBlock);
+        // Expired but the sensor isn't done yet
+        assert_eq!(sensor.poll().unwrap_err(), nb::Error::WouldBlock);
+
+        let reading = sensor.poll().unwrap();
+        assert_eq!(reading.temperature, 72.32318);
+        assert!(!sensor.is_measuring());
+
+        sensor.destroy().done();
+    }
+}
@@ -0,0 +1,330 @@
+//! Drives two sensors sharing one bus on both supported addresses and
+//! cross-validates the readings of redundant sensors
+use crate::{
+    clock::{MaybeClock, NoClock},
+    error::{Result, SHTError, TransitionError, TransitionResult},
+    mode::{Sht31Measure, Sht31Reader},
+    Accuracy, DeviceAddr, Reading, TemperatureUnit, SHT31,
+};
+use embedded_hal::i2c::I2c;
+
+/// Maximum difference between two readings for them to be considered in agreement
+#[derive(Copy, Clone, Debug)]
+pub struct Tolerance {
+    /// Temperature difference, expressed in the first reading's unit
+    pub temperature: f32,
+    /// Relative humidity difference in %RH
+    pub humidity: f32,
+}
+
+impl Reading {
+    /// Averages two readings of redundant sensors, failing with a divergence error if
+    /// they disagree by more than the tolerance. The result is in this reading's unit
+    pub fn consensus(&self, other: &Reading, tolerance: Tolerance) -> Result<Reading> {
+        let other_temperature = self.in_unit(other.celsius());
+
+        if (self.temperature - other_temperature).abs() > tolerance.temperature {
+            return Err(SHTError::TemperatureDivergenceError);
+        }
+        if (self.humidity - other.humidity).abs() > tolerance.humidity {
+            return Err(SHTError::HumidityDivergenceError);
+        }
+
+        Ok(Reading {
+            temperature: (self.temperature + other_temperature) / 2f32,
+            humidity: (self.humidity + other.humidity) / 2f32,
+            unit: self.unit,
+        })
+    }
+}
+
+/// A pair of sensors on [DeviceAddr::AD0] and [DeviceAddr::AD1] sharing the same bus
+/// and configuration, readings are always returned in that order. Each sensor keeps its
+/// own state, its heater, heater guard, health, cached reading and compensation, so
+/// calibrate them one by one through [Sht31Pair::first] and [Sht31Pair::second]
+#[derive(Copy, Clone, Debug)]
+pub struct Sht31Pair<Mode, I2C, C = NoClock> {
+    /// Holds the bus and the state of the sensor on [DeviceAddr::AD0]
+    sensor: SHT31<Mode, I2C, C>,
+    /// The state of the sensor on [DeviceAddr::AD1] while it's off the bus
+    second: SHT31<Mode, ()>,
+}
+
+impl<Mode, I2C, C> Sht31Pair<Mode, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Create a pair from a sensor whose configuration is shared by both devices,
+    /// the sensor's address is ignored. The second device starts without compensation
+    pub fn new(mut sensor: SHT31<Mode, I2C, C>) -> Self
+    where
+        Mode: Clone,
+    {
+        sensor.set_address(DeviceAddr::AD0);
+
+        let mut second = SHT31::with_defaults(sensor.mode.clone(), ());
+        second.address = DeviceAddr::AD1.into();
+        second.accuracy = sensor.accuracy;
+        second.unit = sensor.unit;
+        second.heater_guard = sensor.heater_guard;
+        second.plausibility_check = sensor.plausibility_check;
+        second.checksum = sensor.checksum;
+        second.checksum_policy = sensor.checksum_policy;
+
+        Self { sensor, second }
+    }
+
+    /// Change both sensors' temperature unit
+    pub fn with_unit(mut self, unit: TemperatureUnit) -> Self {
+        self.sensor.set_unit(unit);
+        self.second.set_unit(unit);
+        self
+    }
+
+    /// Change both sensors' accuracy
+    pub fn with_accuracy(mut self, accuracy: Accuracy) -> Self {
+        self.sensor.set_accuracy(accuracy);
+        self.second.set_accuracy(accuracy);
+        self
+    }
+
+    /// Runs the closure against the sensor on [DeviceAddr::AD0], e.g. to calibrate it
+    pub fn first<T>(&mut self, f: impl FnOnce(&mut SHT31<Mode, I2C, C>) -> T) -> T {
+        f(&mut self.sensor)
+    }
+
+    /// Runs the closure against the sensor on [DeviceAddr::AD1], e.g. to calibrate it
+    pub fn second<T>(&mut self, f: impl FnOnce(&mut SHT31<Mode, I2C, C>) -> T) -> T {
+        self.sensor.swap_device(&mut self.second);
+        let result = f(&mut self.sensor);
+        self.sensor.swap_device(&mut self.second);
+        result
+    }
+
+    /// Runs the closure against the sensor on each address
+    fn each<T>(
+        &mut self,
+        mut f: impl FnMut(&mut SHT31<Mode, I2C, C>) -> Result<T>,
+    ) -> Result<(T, T)> {
+        let first = self.first(&mut f)?;
+        let second = self.second(f)?;
+        Ok((first, second))
+    }
+
+    /// Set both heaters' heating state, if the second heater fails to turn on the
+    /// first one is turned back off
+    pub fn set_heating(&mut self, heating: bool) -> Result<()> {
+        self.sensor.set_heating(heating)?;
+
+        let result = self.second(|sensor| sensor.set_heating(heating));
+        if result.is_err() && heating {
+            self.sensor.set_heating(false)?;
+        }
+        result
+    }
+
+    /// Checks that both sensors respond
+    pub fn probe(&mut self) -> Result<()> {
+        self.each(|sensor| sensor.probe()).map(|_| ())
+    }
+
+    /// Consumes the pair and returns the i2c
+    pub fn destroy(self) -> I2C {
+        self.sensor.destroy()
+    }
+}
+
+impl<Mode, I2C, C> Sht31Pair<Mode, I2C, C>
+where
+    I2C: I2c,
+    SHT31<Mode, I2C, C>: Sht31Reader,
+    C: MaybeClock,
+{
+    /// Read both sensors
+    pub fn read(&mut self) -> Result<(Reading, Reading)> {
+        self.each(|sensor| sensor.read())
+    }
+
+    /// Read both sensors and average them, see [Reading::consensus]
+    pub fn read_consensus(&mut self, tolerance: Tolerance) -> Result<Reading> {
+        let (first, second) = self.read()?;
+        first.consensus(&second, tolerance)
+    }
+}
+
+impl<Mode, Measured, I2C, C> Sht31Measure for Sht31Pair<Mode, I2C, C>
+where
+    I2C: I2c,
+    Mode: Copy,
+    Measured: Copy,
+    SHT31<Mode, I2C, C>: Sht31Measure<Measuring = SHT31<Measured, I2C, C>>,
+{
+    type Measuring = Sht31Pair<Measured, I2C, C>;
+
+    /// Initialize reading on both sensors, if the second sensor fails the first one
+    /// may already be measuring
+    fn measure(self) -> TransitionResult<Self::Measuring, Self> {
+        let Self { sensor, mut second } = self;
+        let mode = sensor.mode;
+
+        let mut sensor = match sensor.measure() {
+            Ok(measuring) => measuring.into_mode(mode),
+            Err(err) => return Err(err.map_sensor(|sensor| Self { sensor, second })),
+        };
+
+        sensor.swap_device(&mut second);
+        let mut sensor = match sensor.measure() {
+            Ok(measuring) => measuring,
+            Err(err) => {
+                let mut sensor = err.sensor;
+                sensor.swap_device(&mut second);
+                return Err(TransitionError {
+                    sensor: Self { sensor, second },
+                    error: err.error,
+                });
+            }
+        };
+
+        let mut second = second.into_mode(sensor.mode);
+        sensor.swap_device(&mut second);
+        Ok(Sht31Pair { sensor, second })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use crate::{prelude::*, SHTError};
+    use alloc::vec;
+    use embedded_hal::i2c::ErrorKind;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    const READING: [u8; 6] = [98, 153, 188, 98, 32, 139];
+
+    #[test]
+    fn single_shot_pair() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x24, 0x16]),
+            Transaction::write(0x45, vec![0x24, 0x16]),
+            Transaction::read(0x44, READING.to_vec()),
+            Transaction::read(0x45, READING.to_vec()),
+        ];
+        let sensor = SHT31::single_shot(Mock::new(&expectations), SingleShot::new())
+            .with_address(DeviceAddr::AD1);
+
+        let mut pair = Sht31Pair::new(sensor)
+            .with_accuracy(Accuracy::Low)
+            .measure()
+            .unwrap();
+        let (first, second) = pair.read().unwrap();
+        assert_eq!(first.temperature, 72.32318);
+        assert_eq!(second.temperature, 72.32318);
+
+        pair.destroy().done();
+    }
+
+    #[test]
+    fn periodic_pair() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x21, 0x30]),
+            Transaction::write(0x45, vec![0x21, 0x30]),
+            Transaction::write(0x44, vec![0xE0, 0x00]),
+            Transaction::read(0x44, READING.to_vec()),
+            Transaction::write(0x45, vec![0xE0, 0x00]),
+            Transaction::read(0x45, READING.to_vec()),
+        ];
+        let sensor = SHT31::periodic(Mock::new(&expectations), Periodic::new());
+
+        let mut pair = Sht31Pair::new(sensor).measure().unwrap();
+        let (first, second) = pair.read().unwrap();
+        assert_eq!(first.humidity, second.humidity);
+
+        pair.destroy().done();
+    }
+
+    #[test]
+    fn separate_state() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x21, 0x30]),
+            Transaction::write(0x45, vec![0x21, 0x30]),
+            Transaction::write(0x44, vec![0xE0, 0x00]),
+            Transaction::read(0x44, READING.to_vec()),
+            Transaction::write(0x45, vec![0xE0, 0x00]),
+            Transaction::read(0x45, READING.to_vec()),
+        ];
+        let sensor = SHT31::periodic(Mock::new(&expectations), Periodic::new())
+            .with_unit(TemperatureUnit::Celsius)
+            .with_offsets(1f32, 0f32);
+
+        let mut pair = Sht31Pair::new(sensor).measure().unwrap();
+        pair.second(|sensor| sensor.set_offsets(-1f32, 2f32));
+        let (first, second) = pair.read().unwrap();
+        assert!((first.temperature - second.temperature - 2f32).abs() < 0.001);
+        assert!((second.humidity - first.humidity - 2f32).abs() < 0.001);
+        assert_eq!(pair.first(|sensor| sensor.address()), 0x44);
+        assert_eq!(pair.second(|sensor| sensor.address()), 0x45);
+
+        pair.destroy().done();
+    }
+
+    #[test]
+    fn heating_failure() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x30, 0x6D]),
+            Transaction::write(0x45, vec![0x30, 0x6D]).with_error(ErrorKind::Other),
+            // The first heater doesn't keep running alone
+            Transaction::write(0x44, vec![0x30, 0x66]),
+        ];
+        let sensor = SHT31::single_shot(Mock::new(&expectations), SingleShot::new());
+
+        let mut pair = Sht31Pair::new(sensor);
+        assert_eq!(
+            pair.set_heating(true).err().unwrap(),
+            SHTError::WriteI2CError
+        );
+
+        pair.destroy().done();
+    }
+
+    #[test]
+    fn consensus() {
+        let tolerance = Tolerance {
+            temperature: 0.5,
+            humidity: 2f32,
+        };
+        let celsius = Reading {
+            temperature: 20f32,
+            humidity: 50f32,
+            unit: TemperatureUnit::Celsius,
+        };
+        let fahrenheit = Reading {
+            temperature: 68.5,
+            humidity: 51f32,
+            unit: TemperatureUnit::Fahrenheit,
+        };
+
+        let average = celsius.consensus(&fahrenheit, tolerance).unwrap();
+        assert!((average.temperature - 20.14).abs() < 0.01);
+        assert_eq!(average.humidity, 50.5);
+        assert_eq!(average.unit, TemperatureUnit::Celsius);
+
+        let drifted = Reading {
+            temperature: 21f32,
+            ..celsius
+        };
+        assert_eq!(
+            celsius.consensus(&drifted, tolerance).err().unwrap(),
+            SHTError::TemperatureDivergenceError
+        );
+
+        let drifted = Reading {
+            humidity: 55f32,
+            ..celsius
+        };
+        assert_eq!(
+            celsius.consensus(&drifted, tolerance).err().unwrap(),
+            SHTError::HumidityDivergenceError
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Average supply current estimates for battery budgeting, based on the datasheet's
+//! typical figures at 3.3 V
+use crate::{
+    mode::{ClockStretch, Periodic, SimpleSingleShot, SingleShot, MPS},
+    Accuracy, SHT31,
+};
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+
+/// Idle current while no periodic measurements are running
+const IDLE_SINGLE_SHOT_UA: f32 = 0.2;
+/// Idle current between periodic measurements
+const IDLE_PERIODIC_UA: f32 = 45f32;
+/// Current while a measurement is running
+const MEASURING_UA: f32 = 600f32;
+/// Current drawn by the heater
+const HEATER_UA: f32 = 3_300f32;
+
+/// Average current for measurements taken `per_second` times a second, the
+/// measurement time is the accuracy's worst case so the estimate errs high
+fn average_current_ua(accuracy: Accuracy, per_second: f32, idle_ua: f32, heater: bool) -> f32 {
+    let duty = (per_second * accuracy.max_duration_us() as f32 / 1_000_000f32).min(1f32);
+    let heater_ua = if heater { HEATER_UA } else { 0f32 };
+
+    idle_ua * (1f32 - duty) + MEASURING_UA * duty + heater_ua
+}
+
+impl MPS {
+    /// Measurements taken per second
+    pub(crate) fn per_second(&self) -> f32 {
+        match self {
+            MPS::Half => 0.5,
+            MPS::Normal => 1f32,
+            MPS::Double => 2f32,
+            MPS::X4 => 4f32,
+            MPS::X10 => 10f32,
+        }
+    }
+}
+
+impl<I2C, State, C> SHT31<Periodic<State>, I2C, C>
+where
+    I2C: I2c,
+{
+    /// Estimated average supply current in µA while measuring periodically,
+    /// ART is estimated at the highest repeatability
+    pub fn estimated_current_ua(&self) -> f32 {
+        average_current_ua(
+            self.effective_accuracy().unwrap_or(Accuracy::High),
+            self.mode.effective_mps().per_second(),
+            IDLE_PERIODIC_UA,
+            self.heater,
+        )
+    }
+}
+
+impl<I2C, State, C> SHT31<SingleShot<State>, I2C, C>
+where
+    I2C: I2c,
+{
+    /// Estimated average supply current in µA when reading `per_second` times a second
+    pub fn estimated_current_ua(&self, per_second: f32) -> f32 {
+        average_current_ua(self.accuracy, per_second, IDLE_SINGLE_SHOT_UA, self.heater)
+    }
+}
+
+impl<I2C, C> SHT31<ClockStretch, I2C, C>
+where
+    I2C: I2c,
+{
+    /// Estimated average supply current in µA when reading `per_second` times a second
+    pub fn estimated_current_ua(&self, per_second: f32) -> f32 {
+        average_current_ua(self.accuracy, per_second, IDLE_SINGLE_SHOT_UA, self.heater)
+    }
+}
+
+impl<I2C, D, Y, C> SHT31<SimpleSingleShot<D, Y>, I2C, C>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    /// Estimated average supply current in µA when reading `per_second` times a second
+    pub fn estimated_current_ua(&self, per_second: f32) -> f32 {
+        average_current_ua(self.accuracy, per_second, IDLE_SINGLE_SHOT_UA, self.heater)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+    use embedded_hal_mock::eh1::i2c::Mock;
+
+    #[test]
+    fn single_shot() {
+        let sensor =
+            SHT31::single_shot(Mock::new(&[]), SingleShot::new()).with_accuracy(Accuracy::Low);
+
+        // 4.5 ms of 600 µA every second
+        let estimate = sensor.estimated_current_ua(1f32);
+        assert!((estimate - 2.899).abs() < 0.001);
+        assert!(sensor.estimated_current_ua(0.1) < estimate);
+
+        sensor.destroy().done();
+    }
+
+    #[test]
+    fn periodic() {
+        let sensor = SHT31::periodic(Mock::new(&[]), Periodic::new().with_mps(MPS::X10));
+
+        // 15.5 ms of 600 µA ten times a second
+        let estimate = sensor.estimated_current_ua();
+        assert!((estimate - 131.025).abs() < 0.001);
+
+        sensor.destroy().done();
+    }
+}
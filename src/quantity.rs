@@ -0,0 +1,79 @@
+//! Readings as the quantity types of unit crates, which carry their unit so it can't be
+//! mixed up downstream
+use crate::{Reading, TemperatureUnit};
+#[cfg(feature = "uom")]
+use uom::si::{
+    f32::{Ratio, ThermodynamicTemperature},
+    ratio::percent,
+    thermodynamic_temperature::{degree_celsius, degree_fahrenheit},
+};
+
+#[cfg(feature = "uom")]
+impl Reading {
+    /// The temperature as a quantity, whichever unit the reading is in
+    pub fn thermodynamic_temperature(&self) -> ThermodynamicTemperature {
+        match self.unit {
+            TemperatureUnit::Celsius => {
+                ThermodynamicTemperature::new::<degree_celsius>(self.temperature)
+            }
+            TemperatureUnit::Fahrenheit => {
+                ThermodynamicTemperature::new::<degree_fahrenheit>(self.temperature)
+            }
+        }
+    }
+
+    /// The relative humidity as a ratio
+    pub fn relative_humidity(&self) -> Ratio {
+        Ratio::new::<percent>(self.humidity)
+    }
+}
+
+#[cfg(feature = "measurements")]
+impl From<Reading> for measurements::Temperature {
+    fn from(reading: Reading) -> Self {
+        let temperature = reading.temperature as f64;
+        match reading.unit {
+            TemperatureUnit::Celsius => Self::from_celsius(temperature),
+            TemperatureUnit::Fahrenheit => Self::from_fahrenheit(temperature),
+        }
+    }
+}
+
+#[cfg(feature = "measurements")]
+impl From<Reading> for measurements::Humidity {
+    fn from(reading: Reading) -> Self {
+        Self::from_percent(reading.humidity as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const READING: Reading = Reading {
+        temperature: 72.32318,
+        humidity: 38.33066,
+        unit: TemperatureUnit::Fahrenheit,
+    };
+
+    #[cfg(feature = "uom")]
+    #[test]
+    fn uom() {
+        use uom::si::{ratio::ratio, thermodynamic_temperature::kelvin};
+
+        let temperature = READING.thermodynamic_temperature();
+        assert!((temperature.get::<degree_celsius>() - 22.40177).abs() < 0.001);
+        assert!((temperature.get::<kelvin>() - 295.55177).abs() < 0.001);
+        assert!((READING.relative_humidity().get::<ratio>() - 0.3833066).abs() < 0.0001);
+    }
+
+    #[cfg(feature = "measurements")]
+    #[test]
+    fn measurements() {
+        let temperature = measurements::Temperature::from(READING);
+        assert!((temperature.as_celsius() - 22.40177).abs() < 0.001);
+
+        let humidity = measurements::Humidity::from(READING);
+        assert!((humidity.as_ratio() - 0.3833066).abs() < 0.0001);
+    }
+}
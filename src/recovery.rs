@@ -0,0 +1,298 @@
+//! I2C bus recovery for when the sensor holds SDA low after an interrupted transaction,
+//! and recovery of a sensor that keeps failing to deliver valid readings
+use crate::{
+    clock::{MaybeClock, NoClock},
+    error::{Result, SHTError},
+    mode::{Sht31Reader, Sht31Sensor},
+    Reading, SHT31,
+};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+    i2c::I2c,
+};
+
+/// Half of a 100 kHz SCL period
+const HALF_PERIOD_US: u32 = 5;
+/// Clock pulses needed to shift out any byte the sensor is stuck sending
+const RECOVERY_PULSES: u8 = 9;
+
+/// Clocks SCL until the sensor releases SDA, up to 9 pulses, then issues a STOP condition.
+/// Both pins must be open drain and the I2C peripheral must not be driving them.
+/// Fails with [SHTError::BusStuckError] if SDA is still held low
+pub fn recover_bus<SCL, SDA, D>(scl: &mut SCL, sda: &mut SDA, delay: &mut D) -> Result<()>
+where
+    SCL: OutputPin,
+    SDA: OutputPin + InputPin,
+    D: DelayNs,
+{
+    sda.set_high().map_err(|_| SHTError::PinError)?;
+    scl.set_high().map_err(|_| SHTError::PinError)?;
+    delay.delay_us(HALF_PERIOD_US);
+
+    for _ in 0..RECOVERY_PULSES {
+        if sda.is_high().map_err(|_| SHTError::PinError)? {
+            break;
+        }
+        scl.set_low().map_err(|_| SHTError::PinError)?;
+        delay.delay_us(HALF_PERIOD_US);
+        scl.set_high().map_err(|_| SHTError::PinError)?;
+        delay.delay_us(HALF_PERIOD_US);
+    }
+
+    if sda.is_low().map_err(|_| SHTError::PinError)? {
+        return Err(SHTError::BusStuckError);
+    }
+
+    // STOP, SDA rises while SCL is high
+    scl.set_low().map_err(|_| SHTError::PinError)?;
+    delay.delay_us(HALF_PERIOD_US);
+    sda.set_low().map_err(|_| SHTError::PinError)?;
+    delay.delay_us(HALF_PERIOD_US);
+    scl.set_high().map_err(|_| SHTError::PinError)?;
+    delay.delay_us(HALF_PERIOD_US);
+    sda.set_high().map_err(|_| SHTError::PinError)?;
+    delay.delay_us(HALF_PERIOD_US);
+
+    Ok(())
+}
+
+/// Re-issues whatever command a mode depends on, needed after a reset wiped the
+/// sensor's state
+pub trait Rearm {
+    fn rearm(&mut self) -> Result<()>;
+}
+
+/// A reading and whether the sensor had to be recovered to get it
+#[derive(Copy, Clone, Debug)]
+pub enum ReadOutcome {
+    Read(Reading),
+    /// The sensor was soft reset and re-armed before this reading
+    Recovered(Reading),
+}
+
+impl ReadOutcome {
+    pub fn reading(&self) -> Reading {
+        match self {
+            ReadOutcome::Read(reading) | ReadOutcome::Recovered(reading) => *reading,
+        }
+    }
+}
+
+/// Whether the error means the sensor's output can't be trusted. A sensor that has no
+/// data ready yet is working fine, one that never got any ready within a timeout isn't
+fn is_read_failure(error: &SHTError) -> bool {
+    matches!(
+        error,
+        SHTError::ReadI2CError
+            | SHTError::WriteI2CError
+            | SHTError::WriteReadI2CError
+            | SHTError::InvalidTemperatureChecksumError { .. }
+            | SHTError::InvalidHumidityChecksumError { .. }
+            | SHTError::ReadingTimeoutError { .. }
+            | SHTError::FetchTimeoutError
+    )
+}
+
+/// Soft resets and re-arms the sensor once `max_failures` consecutive reads failed
+/// with a CRC, I2C or timeout error, then retries once with [Sht31Sensor::sample] so a periodic
+/// sensor gets the time to measure again
+#[derive(Copy, Clone, Debug)]
+pub struct Recovering<Mode, I2C, D, C = NoClock> {
+    sensor: SHT31<Mode, I2C, C>,
+    delay: D,
+    max_failures: u8,
+    failures: u8,
+}
+
+impl<Mode, I2C, D, C> Recovering<Mode, I2C, D, C>
+where
+    I2C: I2c,
+    D: DelayNs,
+    SHT31<Mode, I2C, C>: Sht31Reader + Sht31Sensor + Rearm,
+    C: MaybeClock,
+{
+    pub fn new(sensor: SHT31<Mode, I2C, C>, delay: D, max_failures: u8) -> Self {
+        Self {
+            sensor,
+            delay,
+            max_failures,
+            failures: 0,
+        }
+    }
+
+    /// The wrapped sensor
+    pub fn inner_mut(&mut self) -> &mut SHT31<Mode, I2C, C> {
+        &mut self.sensor
+    }
+
+    /// Consumes the policy and returns the sensor
+    pub fn into_inner(self) -> SHT31<Mode, I2C, C> {
+        self.sensor
+    }
+
+    /// Read the sensor, failures below the limit are returned as is
+    pub fn read(&mut self) -> Result<ReadOutcome> {
+        let error = match self.sensor.read() {
+            Ok(reading) => {
+                self.failures = 0;
+                return Ok(ReadOutcome::Read(reading));
+            }
+            Err(error) => error,
+        };
+
+        if !is_read_failure(&error) {
+            return Err(error);
+        }
+        self.failures = self.failures.saturating_add(1);
+        if self.failures < self.max_failures {
+            return Err(error);
+        }
+
+        self.failures = 0;
+        self.sensor.soft_reset_and_wait(&mut self.delay)?;
+        self.sensor.rearm()?;
+        self.sensor
+            .sample(&mut self.delay)
+            .map(ReadOutcome::Recovered)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use super::*;
+    use alloc::vec::Vec;
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::digital::{Mock, State, Transaction};
+    use embedded_hal_mock::eh1::i2c;
+
+    fn stop_sequence() -> ([Transaction; 2], [Transaction; 2]) {
+        (
+            [Transaction::set(State::Low), Transaction::set(State::High)],
+            [Transaction::set(State::Low), Transaction::set(State::High)],
+        )
+    }
+
+    #[test]
+    fn releases_after_pulses() {
+        let (scl_stop, sda_stop) = stop_sequence();
+
+        let mut scl_expectations = Vec::from([Transaction::set(State::High)]);
+        for _ in 0..2 {
+            scl_expectations.push(Transaction::set(State::Low));
+            scl_expectations.push(Transaction::set(State::High));
+        }
+        scl_expectations.extend(scl_stop);
+
+        let mut sda_expectations = Vec::from([
+            Transaction::set(State::High),
+            Transaction::get(State::Low),
+            Transaction::get(State::Low),
+            Transaction::get(State::High),
+            Transaction::get(State::High),
+        ]);
+        sda_expectations.extend(sda_stop);
+
+        let mut scl = Mock::new(&scl_expectations);
+        let mut sda = Mock::new(&sda_expectations);
+
+        recover_bus(&mut scl, &mut sda, &mut NoopDelay).unwrap();
+
+        scl.done();
+        sda.done();
+    }
+
+    #[test]
+    fn stuck_bus() {
+        let mut scl_expectations = Vec::from([Transaction::set(State::High)]);
+        let mut sda_expectations = Vec::from([Transaction::set(State::High)]);
+        for _ in 0..RECOVERY_PULSES {
+            scl_expectations.push(Transaction::set(State::Low));
+            scl_expectations.push(Transaction::set(State::High));
+            sda_expectations.push(Transaction::get(State::Low));
+        }
+        sda_expectations.push(Transaction::get(State::Low));
+
+        let mut scl = Mock::new(&scl_expectations);
+        let mut sda = Mock::new(&sda_expectations);
+
+        assert_eq!(
+            recover_bus(&mut scl, &mut sda, &mut NoopDelay),
+            Err(SHTError::BusStuckError)
+        );
+
+        scl.done();
+        sda.done();
+    }
+
+    #[test]
+    fn recovers_after_repeated_failures() {
+        use crate::prelude::*;
+
+        let corrupted = [98, 153, 0, 98, 32, 139];
+        let reading = [98, 153, 188, 98, 32, 139];
+        let fetch = || i2c::Transaction::write(0x44, [0xE0, 0x00].to_vec());
+        let expectations = [
+            i2c::Transaction::write(0x44, [0x21, 0x30].to_vec()),
+            fetch(),
+            i2c::Transaction::read(0x44, corrupted.to_vec()),
+            fetch(),
+            i2c::Transaction::read(0x44, corrupted.to_vec()),
+            i2c::Transaction::write(0x44, [0x30, 0xA2].to_vec()),
+            i2c::Transaction::write(0x44, [0x21, 0x30].to_vec()),
+            // Nothing measured yet right after re-arming
+            fetch(),
+            crate::test_utils::not_ready(0x44),
+            fetch(),
+            i2c::Transaction::read(0x44, reading.to_vec()),
+            fetch(),
+            i2c::Transaction::read(0x44, reading.to_vec()),
+        ];
+        let sensor = SHT31::periodic(i2c::Mock::new(&expectations), Periodic::new())
+            .measure()
+            .unwrap();
+        let mut sensor = Recovering::new(sensor, NoopDelay, 2);
+
+        assert!(matches!(
+            sensor.read(),
+            Err(SHTError::InvalidTemperatureChecksumError { .. })
+        ));
+        assert!(matches!(sensor.read(), Ok(ReadOutcome::Recovered(_))));
+
+        let outcome = sensor.read().unwrap();
+        assert!(matches!(outcome, ReadOutcome::Read(_)));
+        assert_eq!(outcome.reading().temperature, 72.32318);
+
+        sensor.into_inner().destroy().done();
+    }
+
+    #[test]
+    fn recovers_after_repeated_timeouts() {
+        use crate::prelude::*;
+
+        let measure = || i2c::Transaction::write(0x44, [0x2C, 0x06].to_vec());
+        let expectations = [
+            measure(),
+            crate::test_utils::not_ready(0x44),
+            measure(),
+            crate::test_utils::not_ready(0x44),
+            i2c::Transaction::write(0x44, [0x30, 0xA2].to_vec()),
+            measure(),
+            i2c::Transaction::read(0x44, [98, 153, 188, 98, 32, 139].to_vec()),
+        ];
+        let mode = SimpleSingleShot::new(NoopDelay).with_max_retries(1);
+        let sensor = SHT31::simple_single_shot(i2c::Mock::new(&expectations), mode);
+        let mut sensor = Recovering::new(sensor, NoopDelay, 2);
+
+        assert_eq!(
+            sensor.read().err().unwrap(),
+            SHTError::ReadingTimeoutError { attempts: 1 }
+        );
+        let outcome = sensor.read().unwrap();
+        assert!(matches!(outcome, ReadOutcome::Recovered(_)));
+        assert_eq!(outcome.reading().temperature, 72.32318);
+
+        sensor.into_inner().destroy().done();
+    }
+}
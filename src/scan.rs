@@ -0,0 +1,60 @@
+//! Bus scanning for bring-up tooling and hot-plugged probe detection
+use crate::{DeviceAddr, SHT31};
+use embedded_hal::i2c::I2c;
+
+/// A sensor that responded during a [scan]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ScannedDevice {
+    pub address: DeviceAddr,
+    /// [None] if the serial number couldn't be read
+    pub serial_number: Option<u32>,
+}
+
+/// Probes both supported addresses, returning the sensors found on
+/// [DeviceAddr::AD0] and [DeviceAddr::AD1] respectively.
+/// A `&mut` bus can be passed to keep using it afterwards
+pub fn scan<I2C: I2c>(i2c: I2C) -> [Option<ScannedDevice>; 2] {
+    let mut sensor = SHT31::idle(i2c);
+
+    [DeviceAddr::AD0, DeviceAddr::AD1].map(|address| {
+        sensor.set_address(address);
+        sensor.is_connected().then(|| ScannedDevice {
+            address,
+            serial_number: sensor.serial_number().ok(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use super::*;
+    use alloc::vec;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    #[test]
+    fn scan_both_addresses() {
+        let expectations = [
+            Transaction::write_read(0x44, vec![0xF3, 0x2D], vec![0x80, 0x10, 0xE1]),
+            Transaction::write_read(0x44, vec![0x37, 0x80], vec![98, 153, 188, 98, 32, 139]),
+            Transaction::write_read(0x45, vec![0xF3, 0x2D], vec![0; 3])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+        ];
+        let mut i2c = Mock::new(&expectations);
+
+        let found = scan(&mut i2c);
+        assert_eq!(
+            found,
+            [
+                Some(ScannedDevice {
+                    address: DeviceAddr::AD0,
+                    serial_number: Some(0x62996220),
+                }),
+                None
+            ]
+        );
+
+        i2c.done();
+    }
+}
@@ -0,0 +1,104 @@
+//! The `embedded-sensors-hal` traits, so the sensor can be swapped for other environmental
+//! sensors behind them. Every call takes a full reading, use [Sht31Reader::read] directly
+//! when both values are needed. The async traits share the blocking bus transfers
+use crate::{mode::Sht31Reader, SHTError, SHT31};
+use embedded_sensors_hal::{
+    humidity::{Percentage, RelativeHumiditySensor},
+    sensor::{Error, ErrorKind, ErrorType},
+    temperature::{DegreesCelsius, TemperatureSensor},
+};
+use embedded_sensors_hal_async::{
+    humidity::RelativeHumiditySensor as AsyncRelativeHumiditySensor,
+    temperature::TemperatureSensor as AsyncTemperatureSensor,
+};
+
+impl Error for SHTError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            SHTError::DataNotReadyError
+            | SHTError::FetchTimeoutError
+            | SHTError::ReadingTimeoutError { .. } => ErrorKind::NotReady,
+            SHTError::ReadI2CError
+            | SHTError::WriteI2CError
+            | SHTError::WriteReadI2CError
+            | SHTError::InvalidTemperatureChecksumError { .. }
+            | SHTError::InvalidHumidityChecksumError { .. }
+            | SHTError::InvalidStatusChecksumError { .. }
+            | SHTError::InvalidSerialNumberChecksumError { .. }
+            | SHTError::DeviceNotFoundError
+            | SHTError::BusStuckError
+            | SHTError::CommandTimeoutError
+            | SHTError::PinError => ErrorKind::Peripheral,
+            SHTError::UnsupportedAddressError
+            | SHTError::BufferTooSmallError
+            | SHTError::UnsupportedFrequencyError => ErrorKind::InvalidInput,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl<Mode, I2C, C> ErrorType for SHT31<Mode, I2C, C> {
+    type Error = SHTError;
+}
+
+impl<Mode, I2C, C> TemperatureSensor for SHT31<Mode, I2C, C>
+where
+    SHT31<Mode, I2C, C>: Sht31Reader,
+{
+    fn temperature(&mut self) -> Result<DegreesCelsius, Self::Error> {
+        self.read().map(|reading| reading.celsius())
+    }
+}
+
+impl<Mode, I2C, C> RelativeHumiditySensor for SHT31<Mode, I2C, C>
+where
+    SHT31<Mode, I2C, C>: Sht31Reader,
+{
+    fn relative_humidity(&mut self) -> Result<Percentage, Self::Error> {
+        self.read().map(|reading| reading.humidity)
+    }
+}
+
+impl<Mode, I2C, C> AsyncTemperatureSensor for SHT31<Mode, I2C, C>
+where
+    SHT31<Mode, I2C, C>: Sht31Reader,
+{
+    async fn temperature(&mut self) -> Result<DegreesCelsius, Self::Error> {
+        TemperatureSensor::temperature(self)
+    }
+}
+
+impl<Mode, I2C, C> AsyncRelativeHumiditySensor for SHT31<Mode, I2C, C>
+where
+    SHT31<Mode, I2C, C>: Sht31Reader,
+{
+    async fn relative_humidity(&mut self) -> Result<Percentage, Self::Error> {
+        RelativeHumiditySensor::relative_humidity(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{prelude::*, test_utils::single_shot_expectations};
+    use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::Mock};
+    use futures::executor::block_on;
+
+    #[test]
+    fn sensor_traits() {
+        let frame = [98, 153, 188, 98, 32, 139];
+        let [command, read] = single_shot_expectations(0x44, Accuracy::High, true, frame);
+        let expectations = [command.clone(), read.clone(), command, read];
+        let mut sensor = SHT31::new(Mock::new(&expectations), NoopDelay);
+
+        // Always in Celsius regardless of the configured unit
+        let celsius = TemperatureSensor::temperature(&mut sensor).unwrap();
+        assert!((celsius - 22.40177).abs() < 0.0001);
+        let humidity = block_on(AsyncRelativeHumiditySensor::relative_humidity(&mut sensor));
+        assert_eq!(humidity.unwrap(), 38.33066);
+
+        assert_eq!(SHTError::DataNotReadyError.kind(), ErrorKind::NotReady);
+
+        sensor.destroy().done();
+    }
+}
@@ -0,0 +1,74 @@
+//! Constructors for sensors sharing their bus through `embedded-hal-bus`, the other
+//! modes take the devices as is, e.g. `SHT31::periodic(RefCellDevice::new(&bus), Periodic::new())`
+use crate::{mode::SimpleSingleShot, SHT31};
+use core::cell::RefCell;
+use critical_section::Mutex;
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+use embedded_hal_bus::{
+    i2c::{AtomicDevice, CriticalSectionDevice, RefCellDevice},
+    util::AtomicCell,
+};
+
+impl<'a, BUS, D> SHT31<SimpleSingleShot<D>, RefCellDevice<'a, BUS>>
+where
+    BUS: I2c,
+    D: DelayNs,
+{
+    /// Create a new sensor on a bus shared within a single execution context
+    pub fn new_ref_cell(bus: &'a RefCell<BUS>, delay: D) -> Self {
+        Self::new(RefCellDevice::new(bus), delay)
+    }
+}
+
+impl<'a, BUS, D> SHT31<SimpleSingleShot<D>, CriticalSectionDevice<'a, BUS>>
+where
+    BUS: I2c,
+    D: DelayNs,
+{
+    /// Create a new sensor on a bus shared across interrupt priority levels, every
+    /// transaction runs in a critical section
+    pub fn new_critical_section(bus: &'a Mutex<RefCell<BUS>>, delay: D) -> Self {
+        Self::new(CriticalSectionDevice::new(bus), delay)
+    }
+}
+
+impl<'a, BUS, D> SHT31<SimpleSingleShot<D>, AtomicDevice<'a, BUS>>
+where
+    BUS: I2c,
+    D: DelayNs,
+{
+    /// Create a new sensor on a bus shared across interrupt priority levels, a
+    /// transaction fails instead of blocking when the bus is busy
+    pub fn new_atomic(bus: &'a AtomicCell<BUS>, delay: D) -> Self {
+        Self::new(AtomicDevice::new(bus), delay)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use crate::prelude::*;
+    use alloc::vec;
+    use core::cell::RefCell;
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock, Transaction},
+    };
+
+    #[test]
+    fn ref_cell() {
+        let expectations = [
+            Transaction::write_read(0x44, vec![0xF3, 0x2D], vec![0x80, 0x10, 0xE1]),
+            Transaction::write_read(0x45, vec![0xF3, 0x2D], vec![0x00, 0x00, 0x81]),
+        ];
+        let bus = RefCell::new(Mock::new(&expectations));
+
+        let mut indoor = SHT31::new_ref_cell(&bus, NoopDelay);
+        let mut outdoor = SHT31::new_ref_cell(&bus, NoopDelay).with_address(DeviceAddr::AD1);
+
+        assert!(indoor.status().unwrap().system_reset);
+        assert!(!outdoor.status().unwrap().system_reset);
+
+        bus.into_inner().done();
+    }
+}
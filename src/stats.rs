@@ -0,0 +1,162 @@
+//! Running statistics over readings without storing every sample
+use crate::{Reading, TemperatureUnit};
+
+/// Summary of a single channel
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Summary {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    /// Population variance
+    pub variance: f32,
+}
+
+/// Welford accumulator for a single channel
+#[derive(Copy, Clone, Debug)]
+struct Channel {
+    min: f32,
+    max: f32,
+    mean: f32,
+    m2: f32,
+}
+
+impl Channel {
+    const EMPTY: Self = Self {
+        min: f32::INFINITY,
+        max: f32::NEG_INFINITY,
+        mean: 0f32,
+        m2: 0f32,
+    };
+
+    fn push(&mut self, value: f32, count: u32) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+
+        let delta = value - self.mean;
+        self.mean += delta / count as f32;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn summary(&self, count: u32) -> Summary {
+        Summary {
+            min: self.min,
+            max: self.max,
+            mean: self.mean,
+            variance: self.m2 / count as f32,
+        }
+    }
+}
+
+/// Accumulates min, max, mean and variance of both channels since it was created or reset,
+/// resetting it at the end of each period gives per window summaries
+#[derive(Copy, Clone, Debug)]
+pub struct Stats {
+    count: u32,
+    temperature: Channel,
+    humidity: Channel,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self {
+            count: 0,
+            temperature: Channel::EMPTY,
+            humidity: Channel::EMPTY,
+        }
+    }
+
+    /// Feed a reading into the statistics
+    pub fn push(&mut self, reading: &Reading) {
+        self.count += 1;
+        self.temperature.push(reading.temperature, self.count);
+        self.humidity.push(reading.humidity, self.count);
+    }
+
+    /// Forget every reading
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Number of readings fed since the last reset
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// The temperature summary, [None] until a reading has been fed
+    pub fn temperature(&self) -> Option<Summary> {
+        (self.count > 0).then(|| self.temperature.summary(self.count))
+    }
+
+    /// The humidity summary, [None] until a reading has been fed
+    pub fn humidity(&self) -> Option<Summary> {
+        (self.count > 0).then(|| self.humidity.summary(self.count))
+    }
+}
+
+/// The mean of a burst of readings with the spread of each channel
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Averaged {
+    pub mean: Reading,
+    pub temperature: Summary,
+    pub humidity: Summary,
+}
+
+impl Averaged {
+    /// The summary of the fed readings, [None] until a reading has been fed
+    pub(crate) fn from_stats(stats: &Stats, unit: TemperatureUnit) -> Option<Self> {
+        let temperature = stats.temperature()?;
+        let humidity = stats.humidity()?;
+
+        Some(Self {
+            mean: Reading {
+                temperature: temperature.mean,
+                humidity: humidity.mean,
+                unit,
+            },
+            temperature,
+            humidity,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TemperatureUnit;
+
+    #[test]
+    fn stats() {
+        let mut stats = Stats::new();
+        assert!(stats.temperature().is_none());
+
+        for (temperature, humidity) in [(20f32, 40f32), (22f32, 50f32), (24f32, 60f32)] {
+            stats.push(&Reading {
+                temperature,
+                humidity,
+                unit: TemperatureUnit::Celsius,
+            });
+        }
+
+        assert_eq!(stats.count(), 3);
+        let temperature = stats.temperature().unwrap();
+        assert_eq!(temperature.min, 20f32);
+        assert_eq!(temperature.max, 24f32);
+        assert_eq!(temperature.mean, 22f32);
+        assert!((temperature.variance - 8f32 / 3f32).abs() < 0.0001);
+
+        let humidity = stats.humidity().unwrap();
+        assert_eq!(humidity.mean, 50f32);
+        assert!((humidity.variance - 200f32 / 3f32).abs() < 0.001);
+
+        stats.reset();
+        assert_eq!(stats.count(), 0);
+        assert!(stats.humidity().is_none());
+    }
+}
@@ -0,0 +1,84 @@
+//! Periodic measurements as a `futures` [Stream], for consuming readings with the
+//! standard stream combinators.
+//!
+//! Only the waiting goes through the async [DelayNs], the bus is a blocking [I2c] and
+//! polling the stream blocks the executor for every fetch's transfer
+use crate::{
+    clock::MaybeClock,
+    error::Result,
+    mode::{
+        periodic::{FreshFetch, FreshPoll},
+        Measuring, Periodic,
+    },
+    Reading, SHT31,
+};
+use embedded_hal::i2c::I2c;
+use embedded_hal_async::delay::DelayNs;
+use futures_util::{stream, Stream};
+
+impl<I2C, C> SHT31<Periodic<Measuring>, I2C, C>
+where
+    I2C: I2c,
+    C: MaybeClock,
+{
+    /// Waits a sample period and fetches the fresh measurement, polling for up to another
+    /// period before giving up with [SHTError::FetchTimeoutError](crate::SHTError::FetchTimeoutError)
+    async fn next_reading<D: DelayNs>(&mut self, delay: &mut D) -> Result<Reading> {
+        let period_us = self.sample_period_us();
+        delay.delay_us(period_us).await;
+
+        let mut poll = FreshPoll::new(period_us / 1_000);
+        loop {
+            match self.poll_fresh(&mut poll) {
+                FreshFetch::Done(result) => return result,
+                FreshFetch::Retry(ms) => delay.delay_ms(ms).await,
+            }
+        }
+    }
+
+    /// An endless stream of the measurements, waiting a sample period between fetches.
+    /// The bus transfers are the blocking driver's, see the [module](self) docs
+    pub fn into_stream<D: DelayNs>(self, delay: D) -> impl Stream<Item = Result<Reading>> {
+        stream::unfold((self, delay), |(mut sensor, mut delay)| async move {
+            let reading = sensor.next_reading(&mut delay).await;
+            Some((reading, (sensor, delay)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use crate::{prelude::*, SHTError};
+    use alloc::vec;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock, Transaction},
+    };
+    use futures::{executor::block_on, StreamExt};
+
+    #[test]
+    fn stream() {
+        let not_ready = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+        let mut expectations = vec![Transaction::write(0x44, vec![0x21, 0x30])];
+        // Fresh data, then never again within the 1 s period
+        expectations.push(Transaction::write(0x44, vec![0xE0, 0x00]));
+        expectations.push(Transaction::read(0x44, vec![98, 153, 188, 98, 32, 139]));
+        for _ in 0..=100 {
+            expectations.push(Transaction::write(0x44, vec![0xE0, 0x00]));
+            expectations.push(Transaction::read(0x44, vec![0; 6]).with_error(not_ready));
+        }
+        let mut i2c = Mock::new(&expectations);
+
+        let sensor = SHT31::periodic(i2c.clone(), Periodic::new())
+            .measure()
+            .unwrap();
+        let readings: vec::Vec<_> = block_on(sensor.into_stream(NoopDelay).take(2).collect());
+
+        assert_eq!(readings[0].unwrap().temperature, 72.32318);
+        assert_eq!(readings[1].unwrap_err(), SHTError::FetchTimeoutError);
+
+        i2c.done();
+    }
+}
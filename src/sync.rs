@@ -0,0 +1,93 @@
+//! Sharing a sensor between tasks and interrupt handlers, e.g. a task that starts the
+//! periodic measurements while an interrupt fetches them
+use crate::{error::Result, mode::Sht31Reader, Reading};
+use core::cell::RefCell;
+use critical_section::Mutex;
+
+/// A sensor behind a critical section, it starts empty so it can live in a `static`
+/// ```
+/// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+/// use sht31::{mode::Measuring, prelude::*};
+///
+/// static SENSOR: SharedSensor<SHT31<Periodic<Measuring>, Mock>> = SharedSensor::new();
+///
+/// # let i2c = Mock::new(&[
+/// #     Transaction::write(0x44, vec![0x21, 0x30]),
+/// #     Transaction::write(0x44, vec![0xE0, 0x00]),
+/// #     Transaction::read(0x44, vec![98, 153, 188, 98, 32, 139]),
+/// # ]);
+/// let sensor = SHT31::periodic(i2c, Periodic::new()).measure().unwrap();
+/// SENSOR.init(sensor);
+///
+/// // From an interrupt handler
+/// let reading = SENSOR.read();
+/// # SENSOR.take().unwrap().destroy().done();
+/// ```
+pub struct SharedSensor<S> {
+    sensor: Mutex<RefCell<Option<S>>>,
+}
+
+impl<S> SharedSensor<S> {
+    pub const fn new() -> Self {
+        Self {
+            sensor: Mutex::new(RefCell::new(None)),
+        }
+    }
+
+    /// Stores the sensor, handing back the one it replaces
+    pub fn init(&self, sensor: S) -> Option<S> {
+        critical_section::with(|cs| self.sensor.borrow_ref_mut(cs).replace(sensor))
+    }
+
+    /// Takes the sensor out, e.g. to switch its mode before storing it again
+    pub fn take(&self) -> Option<S> {
+        critical_section::with(|cs| self.sensor.borrow_ref_mut(cs).take())
+    }
+
+    /// Runs `f` on the sensor inside a critical section, [None] if there's no sensor stored
+    pub fn lock<R>(&self, f: impl FnOnce(&mut S) -> R) -> Option<R> {
+        critical_section::with(|cs| self.sensor.borrow_ref_mut(cs).as_mut().map(f))
+    }
+}
+
+impl<S> Default for SharedSensor<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Sht31Reader> SharedSensor<S> {
+    /// Reads the stored sensor, [None] if there's no sensor stored
+    pub fn read(&self) -> Option<Result<Reading>> {
+        self.lock(|sensor| sensor.read())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+    use crate::prelude::*;
+    use alloc::vec;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    #[test]
+    fn shared() {
+        let expectations = [
+            Transaction::write(0x44, vec![0x21, 0x30]),
+            Transaction::write(0x44, vec![0xE0, 0x00]),
+            Transaction::read(0x44, vec![98, 153, 188, 98, 32, 139]),
+        ];
+        let shared = SharedSensor::new();
+        assert!(shared.read().is_none());
+
+        // Start measuring from one context
+        let sensor = SHT31::periodic(Mock::new(&expectations), Periodic::new());
+        assert!(shared.init(sensor.measure().unwrap()).is_none());
+
+        // And fetch from another
+        let reading = shared.read().unwrap().unwrap();
+        assert_eq!(reading.temperature, 72.32318);
+
+        shared.take().unwrap().destroy().done();
+    }
+}
@@ -0,0 +1,117 @@
+//! Bus level expectations for `embedded-hal-mock`, so integration tests can mock the
+//! sensor without working out opcodes and checksums by hand
+extern crate alloc;
+use crate::{
+    checksum::{Checksum, SoftwareCrc},
+    command::Command,
+    Accuracy, CELSIUS_PAIR, CONVERSION_DENOM,
+};
+use alloc::vec;
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::i2c::Transaction;
+
+/// A word followed by its checksum
+fn checksummed(word: u16) -> [u8; 3] {
+    let [msb, lsb] = word.to_be_bytes();
+    [msb, lsb, SoftwareCrc::checksum([msb, lsb])]
+}
+
+/// A measurement as the sensor sends it from raw words
+pub fn raw_frame(temperature: u16, humidity: u16) -> [u8; 6] {
+    let [t0, t1, t2] = checksummed(temperature);
+    let [h0, h1, h2] = checksummed(humidity);
+    [t0, t1, t2, h0, h1, h2]
+}
+
+/// A measurement as the sensor sends it, the values are rounded to the sensor's resolution
+pub fn frame(celsius: f32, humidity: f32) -> [u8; 6] {
+    let (offset, span) = CELSIUS_PAIR;
+    let temperature = libm::roundf((celsius + offset) * CONVERSION_DENOM / span);
+    let humidity = libm::roundf(humidity * CONVERSION_DENOM / 100f32);
+    raw_frame(temperature as u16, humidity as u16)
+}
+
+/// Starting a single shot measurement and reading it back
+pub fn single_shot_expectations(
+    address: u8,
+    accuracy: Accuracy,
+    clock_stretch: bool,
+    frame: [u8; 6],
+) -> [Transaction; 2] {
+    let command = Command::SingleShot {
+        accuracy,
+        clock_stretch,
+    };
+    [
+        Transaction::write(address, command.to_bytes().to_vec()),
+        Transaction::read(address, frame.to_vec()),
+    ]
+}
+
+/// Fetching a periodic measurement
+pub fn fetch_expectations(address: u8, frame: [u8; 6]) -> [Transaction; 2] {
+    [
+        Transaction::write(address, Command::FetchData.to_bytes().to_vec()),
+        Transaction::read(address, frame.to_vec()),
+    ]
+}
+
+/// A read the sensor NACKs since it has no data ready
+pub fn not_ready(address: u8) -> Transaction {
+    Transaction::read(address, vec![0; 6])
+        .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+}
+
+/// Reading the status register
+pub fn status_expectation(address: u8, status: u16) -> Transaction {
+    Transaction::write_read(
+        address,
+        Command::ReadStatus.to_bytes().to_vec(),
+        checksummed(status).to_vec(),
+    )
+}
+
+/// Reading the serial number
+pub fn serial_number_expectation(address: u8, serial_number: u32) -> Transaction {
+    let [high, low] = [(serial_number >> 16) as u16, serial_number as u16].map(checksummed);
+    let mut response = high.to_vec();
+    response.extend_from_slice(&low);
+    Transaction::write_read(
+        address,
+        Command::ReadSerialNumber.to_bytes().to_vec(),
+        response,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use embedded_hal_mock::eh1::i2c::Mock;
+
+    #[test]
+    fn frames() {
+        assert_eq!(raw_frame(0x6299, 0x6220), [98, 153, 188, 98, 32, 139]);
+        assert_eq!(raw_frame(0, 0), [0, 0, 0x81, 0, 0, 0x81]);
+
+        let mut expectations = vec![status_expectation(0x44, 0x8010)];
+        expectations.push(serial_number_expectation(0x44, 0x0002_0000));
+        expectations.extend(single_shot_expectations(
+            0x44,
+            Accuracy::High,
+            false,
+            frame(-12.5, 55.25),
+        ));
+        let mut sensor = SHT31::single_shot(Mock::new(&expectations), SingleShot::new())
+            .with_unit(TemperatureUnit::Celsius);
+
+        assert!(sensor.status().unwrap().system_reset);
+        assert_eq!(sensor.serial_number().unwrap(), 0x0002_0000);
+        let mut sensor = sensor.measure().unwrap();
+        let reading = sensor.read().unwrap();
+        assert!((reading.temperature + 12.5).abs() < 0.01);
+        assert!((reading.humidity - 55.25).abs() < 0.01);
+
+        sensor.destroy().done();
+    }
+}
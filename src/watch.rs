@@ -0,0 +1,188 @@
+//! Software threshold alerts for when the ALERT pin isn't wired out
+use crate::Reading;
+
+/// Where a value sits relative to its thresholds
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Level {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// A change of [Level], e.g. entered high or left low
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Edge {
+    pub from: Level,
+    pub to: Level,
+}
+
+impl Edge {
+    /// Whether this edge moved into the level
+    pub fn entered(&self, level: Level) -> bool {
+        self.to == level
+    }
+
+    /// Whether this edge moved out of the level
+    pub fn left(&self, level: Level) -> bool {
+        self.from == level
+    }
+}
+
+/// Thresholds of a single channel, a value has to move back past a threshold by the
+/// hysteresis before its level is left
+#[derive(Copy, Clone, Debug)]
+pub struct Threshold {
+    pub low: f32,
+    pub high: f32,
+    pub hysteresis: f32,
+}
+
+/// Tracks the level of a single channel
+#[derive(Copy, Clone, Debug)]
+struct ChannelWatch {
+    threshold: Threshold,
+    level: Level,
+}
+
+impl ChannelWatch {
+    fn update(&mut self, value: f32) -> Option<Edge> {
+        let Threshold {
+            low,
+            high,
+            hysteresis,
+        } = self.threshold;
+
+        let crossed = if value >= high {
+            Level::High
+        } else if value <= low {
+            Level::Low
+        } else {
+            Level::Normal
+        };
+
+        let level = match self.level {
+            Level::High if value > high - hysteresis => Level::High,
+            Level::Low if value < low + hysteresis => Level::Low,
+            _ => crossed,
+        };
+
+        let from = core::mem::replace(&mut self.level, level);
+        (from != level).then_some(Edge { from, to: level })
+    }
+}
+
+/// Edges produced by a single reading
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Edges {
+    pub temperature: Option<Edge>,
+    pub humidity: Option<Edge>,
+}
+
+/// Compares each reading against thresholds with hysteresis and reports level changes.
+/// Temperature thresholds are in the readings' unit and humidity thresholds in %RH
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Watcher {
+    temperature: Option<ChannelWatch>,
+    humidity: Option<ChannelWatch>,
+}
+
+impl Watcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch the temperature
+    pub fn with_temperature(mut self, threshold: Threshold) -> Self {
+        self.temperature = Some(ChannelWatch {
+            threshold,
+            level: Level::Normal,
+        });
+        self
+    }
+
+    /// Watch the humidity
+    pub fn with_humidity(mut self, threshold: Threshold) -> Self {
+        self.humidity = Some(ChannelWatch {
+            threshold,
+            level: Level::Normal,
+        });
+        self
+    }
+
+    /// Feed a reading, returning the edges it caused
+    pub fn update(&mut self, reading: &Reading) -> Edges {
+        Edges {
+            temperature: self
+                .temperature
+                .as_mut()
+                .and_then(|watch| watch.update(reading.temperature)),
+            humidity: self
+                .humidity
+                .as_mut()
+                .and_then(|watch| watch.update(reading.humidity)),
+        }
+    }
+
+    /// The current temperature level, [None] if it isn't watched
+    pub fn temperature_level(&self) -> Option<Level> {
+        self.temperature.map(|watch| watch.level)
+    }
+
+    /// The current humidity level, [None] if it isn't watched
+    pub fn humidity_level(&self) -> Option<Level> {
+        self.humidity.map(|watch| watch.level)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TemperatureUnit;
+
+    fn reading(temperature: f32) -> Reading {
+        Reading {
+            temperature,
+            humidity: 50f32,
+            unit: TemperatureUnit::Celsius,
+        }
+    }
+
+    #[test]
+    fn hysteresis() {
+        let mut watcher = Watcher::new().with_temperature(Threshold {
+            low: 10f32,
+            high: 30f32,
+            hysteresis: 1f32,
+        });
+
+        assert_eq!(watcher.update(&reading(20f32)), Edges::default());
+
+        let edge = watcher.update(&reading(30f32)).temperature.unwrap();
+        assert!(edge.entered(Level::High));
+
+        // Within the hysteresis band
+        assert_eq!(watcher.update(&reading(29.5)).temperature, None);
+        assert_eq!(watcher.temperature_level(), Some(Level::High));
+
+        let edge = watcher.update(&reading(28f32)).temperature.unwrap();
+        assert!(edge.left(Level::High));
+        assert!(edge.entered(Level::Normal));
+
+        let edge = watcher.update(&reading(5f32)).temperature.unwrap();
+        assert!(edge.entered(Level::Low));
+        assert_eq!(watcher.update(&reading(10.5)).temperature, None);
+
+        // Jumping straight across both thresholds
+        let edge = watcher.update(&reading(35f32)).temperature.unwrap();
+        assert_eq!(
+            edge,
+            Edge {
+                from: Level::Low,
+                to: Level::High
+            }
+        );
+
+        assert_eq!(watcher.humidity_level(), None);
+    }
+}
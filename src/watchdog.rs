@@ -0,0 +1,120 @@
+//! Detection of a hung sensor that keeps returning its last latched measurement
+use crate::{
+    error::{Result, SHTError},
+    mode::Sht31Reader,
+    Reading,
+};
+
+/// Flags a sensor once `limit` consecutive readings are bit-identical, which
+/// usually means it hung and needs a [soft](crate::SHT31::soft_reset) or
+/// [hard](crate::SHT31::hard_reset) reset
+#[derive(Copy, Clone, Debug)]
+pub struct StuckDetector {
+    limit: u8,
+    last: Option<(u32, u32)>,
+    repeats: u8,
+}
+
+impl StuckDetector {
+    pub fn new(limit: u8) -> Self {
+        Self {
+            limit,
+            last: None,
+            repeats: 0,
+        }
+    }
+
+    /// Feed a reading, failing with [SHTError::SensorStuckError] once the limit of
+    /// identical readings is reached. The count starts over afterwards
+    pub fn update(&mut self, reading: &Reading) -> Result<()> {
+        let bits = (reading.temperature.to_bits(), reading.humidity.to_bits());
+
+        if self.last == Some(bits) {
+            self.repeats += 1;
+        } else {
+            self.last = Some(bits);
+            self.repeats = 1;
+        }
+
+        if self.repeats >= self.limit {
+            self.reset();
+            return Err(SHTError::SensorStuckError);
+        }
+        Ok(())
+    }
+
+    /// Forget the previous readings, e.g. after resetting the sensor
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.repeats = 0;
+    }
+}
+
+/// Runs every reading of the wrapped reader through a [StuckDetector]
+#[derive(Copy, Clone, Debug)]
+pub struct Watchdog<R> {
+    reader: R,
+    detector: StuckDetector,
+}
+
+impl<R> Watchdog<R>
+where
+    R: Sht31Reader,
+{
+    pub fn new(reader: R, limit: u8) -> Self {
+        Self {
+            reader,
+            detector: StuckDetector::new(limit),
+        }
+    }
+
+    /// The wrapped reader, e.g. to reset the sensor once it's flagged
+    pub fn inner_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Consumes the watchdog and returns the reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R> Sht31Reader for Watchdog<R>
+where
+    R: Sht31Reader,
+{
+    fn read(&mut self) -> Result<Reading> {
+        let reading = self.reader.read()?;
+        self.detector.update(&reading)?;
+        Ok(reading)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TemperatureUnit;
+
+    fn reading(temperature: f32) -> Reading {
+        Reading {
+            temperature,
+            humidity: 50f32,
+            unit: TemperatureUnit::Celsius,
+        }
+    }
+
+    #[test]
+    fn stuck() {
+        let mut detector = StuckDetector::new(3);
+
+        detector.update(&reading(20f32)).unwrap();
+        detector.update(&reading(20f32)).unwrap();
+        detector.update(&reading(20.01)).unwrap();
+        detector.update(&reading(20.01)).unwrap();
+        assert_eq!(
+            detector.update(&reading(20.01)),
+            Err(SHTError::SensorStuckError)
+        );
+        detector.update(&reading(20.01)).unwrap();
+    }
+}
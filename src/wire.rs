@@ -0,0 +1,213 @@
+//! Fixed-size encodings of readings for radio links, fieldbuses and IoT payloads
+use crate::{error::Result, Reading, SHTError, TemperatureUnit};
+
+/// Cayenne LPP data type of a temperature, `i16` tenths of a °C big-endian
+const LPP_TEMPERATURE: u8 = 0x67;
+/// Cayenne LPP data type of a relative humidity, `u8` in 0.5 %RH steps
+const LPP_HUMIDITY: u8 = 0x68;
+
+/// The value in hundredths, saturating at the bounds of the type
+fn hundredths_i16(value: f32) -> i16 {
+    libm::roundf(value * 100f32) as i16
+}
+
+/// The value in hundredths, saturating at the bounds of the type
+fn hundredths_u16(value: f32) -> u16 {
+    libm::roundf(value * 100f32) as u16
+}
+
+impl Reading {
+    /// Size of an encoded reading
+    pub const ENCODED_LEN: usize = 5;
+
+    /// Encodes the reading as little-endian, in hundredths to stay well below the
+    /// sensor's repeatability:
+    ///
+    /// | bytes | content                                    |
+    /// |-------|--------------------------------------------|
+    /// | 0..2  | temperature, `i16` hundredths of a degree  |
+    /// | 2..4  | humidity, `u16` hundredths of a %RH        |
+    /// | 4     | unit, 0 for Celsius and 1 for Fahrenheit   |
+    ///
+    /// Values outside the encodable range saturate
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let temperature = hundredths_i16(self.temperature);
+        let humidity = hundredths_u16(self.humidity);
+        let unit = match self.unit {
+            TemperatureUnit::Celsius => 0,
+            TemperatureUnit::Fahrenheit => 1,
+        };
+
+        let [t0, t1] = temperature.to_le_bytes();
+        let [h0, h1] = humidity.to_le_bytes();
+        [t0, t1, h0, h1, unit]
+    }
+
+    /// Decodes a reading encoded with [Reading::to_bytes], [None] if the unit is unknown
+    pub fn from_bytes(bytes: [u8; Self::ENCODED_LEN]) -> Option<Reading> {
+        let unit = match bytes[4] {
+            0 => TemperatureUnit::Celsius,
+            1 => TemperatureUnit::Fahrenheit,
+            _ => return None,
+        };
+
+        Some(Reading {
+            temperature: i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 100f32,
+            humidity: u16::from_le_bytes([bytes[2], bytes[3]]) as f32 / 100f32,
+            unit,
+        })
+    }
+
+    /// The conventional Modbus holding register pair, the temperature in hundredths of
+    /// a degree as a two's complement `i16` followed by the humidity in hundredths of a %RH.
+    /// The temperature stays in the reading's unit
+    pub fn to_modbus_registers(&self) -> [u16; 2] {
+        [
+            hundredths_i16(self.temperature) as u16,
+            hundredths_u16(self.humidity),
+        ]
+    }
+
+    /// The BLE Environmental Sensing Temperature characteristic (0x2A6E), a little-endian
+    /// `sint16` in hundredths of a °C regardless of the reading's unit
+    pub fn ess_temperature(&self) -> [u8; 2] {
+        let celsius = self.celsius();
+        let value = if celsius.is_nan() {
+            // Value is not known
+            i16::MIN
+        } else {
+            hundredths_i16(celsius)
+        };
+        value.to_le_bytes()
+    }
+
+    /// The BLE Environmental Sensing Humidity characteristic (0x2A6F), a little-endian
+    /// `uint16` in hundredths of a %RH limited to 0 to 100 %RH
+    pub fn ess_humidity(&self) -> [u8; 2] {
+        let value = if self.humidity.is_nan() {
+            // Value is not known
+            u16::MAX
+        } else {
+            hundredths_u16(self.humidity.clamp(0f32, 100f32))
+        };
+        value.to_le_bytes()
+    }
+
+    /// Size of a Cayenne LPP encoded reading
+    pub const CAYENNE_LPP_LEN: usize = 7;
+
+    /// Writes the reading as Cayenne LPP temperature and humidity items on the given
+    /// channels, returning the written length so more items can be appended after it
+    pub fn write_cayenne_lpp(
+        &self,
+        temperature_channel: u8,
+        humidity_channel: u8,
+        buffer: &mut [u8],
+    ) -> Result<usize> {
+        let payload = buffer
+            .get_mut(..Self::CAYENNE_LPP_LEN)
+            .ok_or(SHTError::BufferTooSmallError)?;
+
+        let [t0, t1] = (libm::roundf(self.celsius() * 10f32) as i16).to_be_bytes();
+        let humidity = libm::roundf(self.humidity * 2f32) as u8;
+        payload.copy_from_slice(&[
+            temperature_channel,
+            LPP_TEMPERATURE,
+            t0,
+            t1,
+            humidity_channel,
+            LPP_HUMIDITY,
+            humidity,
+        ]);
+
+        Ok(Self::CAYENNE_LPP_LEN)
+    }
+
+    /// Inverse of [Reading::to_modbus_registers]
+    pub fn from_modbus_registers(registers: [u16; 2], unit: TemperatureUnit) -> Reading {
+        Reading {
+            temperature: registers[0] as i16 as f32 / 100f32,
+            humidity: registers[1] as f32 / 100f32,
+            unit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let reading = Reading {
+            temperature: -12.345,
+            humidity: 38.33066,
+            unit: TemperatureUnit::Celsius,
+        };
+
+        let bytes = reading.to_bytes();
+        assert_eq!(bytes, [0x2D, 0xFB, 0xF9, 0x0E, 0]);
+
+        let decoded = Reading::from_bytes(bytes).unwrap();
+        assert_eq!(decoded.temperature, -12.35);
+        assert_eq!(decoded.humidity, 38.33);
+        assert_eq!(decoded.unit, TemperatureUnit::Celsius);
+
+        assert!(Reading::from_bytes([0, 0, 0, 0, 2]).is_none());
+    }
+
+    #[test]
+    fn modbus() {
+        let reading = Reading {
+            temperature: -5.5,
+            humidity: 41.25,
+            unit: TemperatureUnit::Fahrenheit,
+        };
+
+        let registers = reading.to_modbus_registers();
+        assert_eq!(registers, [0xFDDA, 4125]);
+
+        let decoded = Reading::from_modbus_registers(registers, TemperatureUnit::Fahrenheit);
+        assert_eq!(decoded.temperature, -5.5);
+        assert_eq!(decoded.humidity, 41.25);
+    }
+
+    #[test]
+    fn ess() {
+        let reading = Reading {
+            temperature: 72.5,
+            humidity: 100.4,
+            unit: TemperatureUnit::Fahrenheit,
+        };
+
+        // 22.5 °C
+        assert_eq!(reading.ess_temperature(), 2250i16.to_le_bytes());
+        assert_eq!(reading.ess_humidity(), 10000u16.to_le_bytes());
+
+        let unknown = Reading {
+            temperature: f32::NAN,
+            humidity: f32::NAN,
+            unit: TemperatureUnit::Celsius,
+        };
+        assert_eq!(unknown.ess_temperature(), [0x00, 0x80]);
+        assert_eq!(unknown.ess_humidity(), [0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn cayenne_lpp() {
+        let reading = Reading {
+            temperature: -4.15,
+            humidity: 38.33066,
+            unit: TemperatureUnit::Celsius,
+        };
+
+        let mut buffer = [0; 8];
+        assert_eq!(reading.write_cayenne_lpp(1, 2, &mut buffer), Ok(7));
+        assert_eq!(buffer, [1, 0x67, 0xFF, 0xD6, 2, 0x68, 77, 0]);
+
+        assert_eq!(
+            reading.write_cayenne_lpp(1, 2, &mut [0; 6]),
+            Err(SHTError::BufferTooSmallError)
+        );
+    }
+}